@@ -16,6 +16,18 @@ fn bench_is_nfc_ascii(b: &mut Bencher) {
     b.iter(|| unicode_normalization::is_nfc(ASCII));
 }
 
+#[bench]
+fn bench_is_nfc_ascii_long(b: &mut Bencher) {
+    let long = ASCII.repeat(100);
+    b.iter(|| unicode_normalization::is_nfc(&long));
+}
+
+#[bench]
+fn bench_is_nfc_latin1_long(b: &mut Bencher) {
+    let long = NFC.repeat(100);
+    b.iter(|| unicode_normalization::is_nfc(&long));
+}
+
 #[bench]
 fn bench_is_nfc_normalized(b: &mut Bencher) {
     b.iter(|| unicode_normalization::is_nfc(NFC));
@@ -71,6 +83,27 @@ fn bench_is_nfd_stream_safe_not_normalized(b: &mut Bencher) {
     b.iter(|| unicode_normalization::is_nfd_stream_safe(NFC));
 }
 
+#[bench]
+fn bench_canonically_equivalent(b: &mut Bencher) {
+    b.iter(|| unicode_normalization::canonically_equivalent(NFC, NFD));
+}
+
+#[bench]
+fn bench_canonically_equivalent_collect(b: &mut Bencher) {
+    b.iter(|| NFC.nfd().collect::<String>() == NFD.nfd().collect::<String>());
+}
+
+#[bench]
+fn bench_nfc_string_push_str(b: &mut Bencher) {
+    b.iter(|| {
+        let mut s = unicode_normalization::NfcString::new();
+        for _ in 0..100 {
+            s.push_str(NFD);
+        }
+        s
+    });
+}
+
 #[bench]
 fn bench_nfc_ascii(b: &mut Bencher) {
     b.iter(|| ASCII.nfc().count());
@@ -93,6 +126,39 @@ fn bench_nfd_long(b: &mut Bencher) {
     b.iter(|| long.nfd().count());
 }
 
+#[bench]
+fn bench_nfc_cow_ascii(b: &mut Bencher) {
+    b.iter(|| unicode_normalization::nfc_cow(ASCII));
+}
+
+#[bench]
+fn bench_nfc_cow_long(b: &mut Bencher) {
+    let long = fs::read_to_string("benches/long.txt").unwrap();
+    b.iter(|| unicode_normalization::nfc_cow(&long).len());
+}
+
+#[bench]
+fn bench_nfc_utf8_ascii_long(b: &mut Bencher) {
+    let long = ASCII.repeat(100);
+    let mut out = Vec::with_capacity(long.len());
+    b.iter(|| {
+        out.clear();
+        unicode_normalization::utf8::nfc(long.as_bytes(), &mut out).unwrap();
+        out.len()
+    });
+}
+
+#[bench]
+fn bench_nfc_utf8_long(b: &mut Bencher) {
+    let long = fs::read_to_string("benches/long.txt").unwrap();
+    let mut out = Vec::with_capacity(long.len());
+    b.iter(|| {
+        out.clear();
+        unicode_normalization::utf8::nfc(long.as_bytes(), &mut out).unwrap();
+        out.len()
+    });
+}
+
 #[bench]
 fn bench_nfkc_ascii(b: &mut Bencher) {
     b.iter(|| ASCII.nfkc().count());
@@ -11,19 +11,45 @@
 # except according to those terms.
 
 # This script uses the following Unicode tables:
+# - CaseFolding.txt
+# - DerivedCoreProperties.txt
 # - DerivedNormalizationProps.txt
+# - HangulSyllableType.txt
+# - NormalizationCorrections.txt
 # - NormalizationTest.txt
+# - PropList.txt
+# - PropertyValueAliases.txt
+# - ScriptExtensions.txt
+# - Scripts.txt
+# - SpecialCasing.txt
 # - UnicodeData.txt
 # - StandardizedVariants.txt
+# - extracted/DerivedBidiClass.txt
+# - extracted/DerivedJoiningType.txt
+#
+# the following IDNA tables:
+# - IdnaMappingTable.txt
+# - IdnaTestV2.txt
+#
+# the following Unicode security tables:
+# - IdentifierStatus.txt
+# - confusables.txt
+#
+# and the stringprep tables of RFC 3454 (rfc3454.txt).
 #
 # Since this should not require frequent updates, we just store this
-# out-of-line and check the tables.rs and normalization_tests.rs files into git.
+# out-of-line and check the tables.rs, normalization_tests.rs and
+# idna_tests.rs files into git.
 import collections
+import re
 import urllib.request
 from itertools import batched
 
 UNICODE_VERSION = "16.0.0"
 UCD_URL = "https://www.unicode.org/Public/%s/ucd/" % UNICODE_VERSION
+IDNA_URL = "https://www.unicode.org/Public/idna/%s/" % UNICODE_VERSION
+RFC_URL = "https://www.rfc-editor.org/rfc/"
+SECURITY_URL = "https://www.unicode.org/Public/security/%s/" % UNICODE_VERSION
 
 PREAMBLE = """// Copyright 2012-2018 The Rust Project Developers. See the COPYRIGHT
 // file at the top-level directory of this distribution and at
@@ -45,6 +71,12 @@ NormalizationTest = collections.namedtuple(
     ["source", "nfc", "nfd", "nfkc", "nfkd"],
 )
 
+IdnaTest = collections.namedtuple(
+    "IdnaTest",
+    ["source", "to_unicode", "to_unicode_status", "to_ascii_n", "to_ascii_n_status",
+     "to_ascii_t", "to_ascii_t_status"],
+)
+
 # Mapping taken from Table 12 from:
 # http://www.unicode.org/reports/tr44/#General_Category_Values
 expanded_categories = {
@@ -59,6 +91,49 @@ expanded_categories = {
     'Cc': ['C'], 'Cf': ['C'], 'Cs': ['C'], 'Co': ['C'], 'Cn': ['C'],
 }
 
+# Bidi_Class values other than the default L, see
+# https://www.unicode.org/reports/tr44/#Bidi_Class_Values
+BIDI_CLASSES = [
+    'AL', 'AN', 'B', 'BN', 'CS', 'EN', 'ES', 'ET', 'FSI', 'LRE', 'LRI', 'LRO',
+    'NSM', 'ON', 'PDF', 'PDI', 'R', 'RLE', 'RLI', 'RLO', 'S', 'WS',
+]
+
+JOINING_TYPES = {'D': 'Dual', 'L': 'Left', 'R': 'Right', 'T': 'Transparent'}
+
+# The exceptions of RFC 5892 section 2.6, which RFC 8264 inherits.
+PRECIS_EXCEPTIONS = {
+    0x00DF: 'PValid', 0x03C2: 'PValid', 0x06FD: 'PValid', 0x06FE: 'PValid',
+    0x0F0B: 'PValid', 0x3007: 'PValid',
+    0x00B7: 'ContextO', 0x0375: 'ContextO', 0x05F3: 'ContextO', 0x05F4: 'ContextO',
+    0x30FB: 'ContextO',
+    0x0640: 'Disallowed', 0x07FA: 'Disallowed', 0x302E: 'Disallowed', 0x302F: 'Disallowed',
+    0x3031: 'Disallowed', 0x3032: 'Disallowed', 0x3033: 'Disallowed', 0x3034: 'Disallowed',
+    0x3035: 'Disallowed', 0x303B: 'Disallowed',
+}
+PRECIS_EXCEPTIONS.update((c, 'ContextO') for c in range(0x0660, 0x066A))
+PRECIS_EXCEPTIONS.update((c, 'ContextO') for c in range(0x06F0, 0x06FA))
+
+# The scripts which get a named index constant, for the CONTEXTO rules of
+# RFC 5892 appendix A and the restriction levels of UTS #39.
+NAMED_SCRIPTS = ('Common', 'Inherited', 'Unknown', 'Latin', 'Greek', 'Cyrillic', 'Hebrew',
+                 'Han', 'Hiragana', 'Katakana', 'Hangul', 'Bopomofo')
+
+# The writing systems which UTS #39 section 5.1 adds to the script sets of
+# Han, Hiragana, Katakana, Hangul and Bopomofo characters.
+AUGMENTED_SCRIPTS = ('Han_with_Bopomofo', 'Japanese', 'Korean')
+
+# The Recommended scripts of UAX #31 table 5, without Common and Inherited.
+RECOMMENDED_SCRIPTS = ('Arabic', 'Armenian', 'Bengali', 'Bopomofo', 'Cyrillic', 'Devanagari',
+                       'Ethiopic', 'Georgian', 'Greek', 'Gujarati', 'Gurmukhi', 'Han', 'Hangul',
+                       'Hebrew', 'Hiragana', 'Kannada', 'Katakana', 'Khmer', 'Lao', 'Latin',
+                       'Malayalam', 'Myanmar', 'Oriya', 'Sinhala', 'Tamil', 'Telugu', 'Thaana',
+                       'Thai', 'Tibetan')
+
+# The tables of RFC 3454 which stringprep uses are pinned to Unicode 3.2,
+# so they are read from the RFC itself rather than from the UCD.
+STRINGPREP_TABLES = ('A.1', 'B.1', 'B.2', 'C.1.2', 'C.2.2', 'C.3', 'C.4', 'C.5',
+                     'C.6', 'C.7', 'C.8', 'C.9', 'D.1', 'D.2')
+
 # Constants from Unicode 9.0.0 Section 3.12 Conjoining Jamo Behavior
 # http://www.unicode.org/versions/Unicode9.0.0/ch03.pdf#M9.32468.Heading.310.Combining.Jamo.Behavior
 S_BASE, L_COUNT, V_COUNT, T_COUNT = 0xAC00, 19, 21, 28
@@ -69,6 +144,21 @@ class UnicodeData(object):
         self._load_unicode_data()
         self.norm_props = self._load_norm_props()
         self.norm_tests = self._load_norm_tests()
+        self.case_folding = self._load_case_folding()
+        self.special_casing = self._load_special_casing()
+        self.core_props = self._load_core_props()
+        self.prop_list = self._load_props("PropList.txt", ('Join_Control', 'Noncharacter_Code_Point'))
+        self.hangul_syllable_types = self._load_derived_ranges("HangulSyllableType.txt", ('L', 'V', 'T'))
+        self.scripts = self._load_derived_ranges("Scripts.txt")
+        self.script_extensions = self._load_script_extensions()
+        self.identifier_status = self._load_derived_ranges("IdentifierStatus.txt", ('Allowed',), SECURITY_URL)
+        self.confusables = self._load_confusables()
+        self.idna_mapping = self._load_idna_mapping()
+        self.idna_tests = self._load_idna_tests()
+        self.bidi_classes = self._load_bidi_classes()
+        self.joining_types = self._load_joining_types()
+        self.stringprep_tables = self._load_stringprep_tables()
+        self.normalization_corrections = self._load_normalization_corrections()
 
         self.canon_comp = self._compute_canonical_comp()
         self.canon_fully_decomp, self.compat_fully_decomp = self._compute_fully_decomposed()
@@ -88,9 +178,10 @@ class UnicodeData(object):
         stats("CJK Compat Variants fully decomp", self.cjk_compat_variants_fully_decomp)
 
         self.ss_leading, self.ss_trailing = self._compute_stream_safe_tables()
+        self.precis_properties = self._compute_precis_properties()
 
-    def _fetch(self, filename):
-        resp = urllib.request.urlopen(UCD_URL + filename)
+    def _fetch(self, filename, base_url=UCD_URL):
+        resp = urllib.request.urlopen(base_url + filename)
         return resp.read().decode('utf-8')
 
     def _load_unicode_data(self):
@@ -100,10 +191,14 @@ class UnicodeData(object):
         self.canon_decomp = {}
         self.general_category_mark = []
         self.general_category_public_assigned = []
+        self.general_category = {}
+        self.width_decomp = {}
+        self.lowercase = {}
 
         assigned_start = 0;
         prev_char_int = -1;
         prev_name = "";
+        line_char_int = -1
 
         for line in self._fetch("UnicodeData.txt").splitlines():
             # See ftp://ftp.unicode.org/Public/3.0-Update/UnicodeData-3.0.0.html
@@ -120,13 +215,24 @@ class UnicodeData(object):
 
             if decomp.startswith('<'):
                 self.compat_decomp[char_int] = [int(c, 16) for c in decomp.split()[1:]]
+                if decomp.split()[0] in ('<wide>', '<narrow>'):
+                    (self.width_decomp[char_int],) = self.compat_decomp[char_int]
             elif decomp != '':
                 self.canon_decomp[char_int] = [int(c, 16) for c in decomp.split()]
 
+            if pieces[13]:
+                self.lowercase[char_int] = int(pieces[13], 16)
+
             if category == 'M' or 'M' in expanded_categories.get(category, []):
                 self.general_category_mark.append(char_int)
 
             assert category != 'Cn', "Unexpected: Unassigned codepoint in UnicodeData.txt"
+            if name.endswith(', Last>'):
+                for c in range(line_char_int + 1, char_int):
+                    self.general_category[c] = category
+            self.general_category[char_int] = category
+            line_char_int = char_int
+
             if category not in ['Co', 'Cs']:
                 if char_int != prev_char_int + 1 and not is_first_and_last(prev_name, name):
                     self.general_category_public_assigned.append((assigned_start, prev_char_int))
@@ -194,6 +300,210 @@ class UnicodeData(object):
 
         return props
 
+    def _load_props(self, filename, names):
+        props = collections.defaultdict(list)
+
+        for line in self._fetch(filename).splitlines():
+            (prop_data, _, _) = line.partition("#")
+            prop_pieces = prop_data.split(";")
+
+            if len(prop_pieces) != 2:
+                continue
+
+            prop = prop_pieces[1].strip()
+            if prop not in names:
+                continue
+
+            (low, _, high) = prop_pieces[0].strip().partition("..")
+            low = int(low, 16)
+            high = int(high, 16) if high else low
+
+            ranges = props[prop]
+            if ranges and ranges[-1][1] + 1 == low:
+                ranges[-1] = (ranges[-1][0], high)
+            else:
+                ranges.append((low, high))
+
+        return props
+
+    def _load_core_props(self):
+        return self._load_props("DerivedCoreProperties.txt",
+            ('XID_Start', 'XID_Continue', 'Default_Ignorable_Code_Point', 'Cased',
+             'Case_Ignorable'))
+
+    def _load_case_folding(self):
+        case_folding = collections.defaultdict(dict)
+
+        for line in self._fetch("CaseFolding.txt").splitlines():
+            (data, _, _) = line.partition("#")
+            pieces = data.split(";")
+            if len(pieces) < 3:
+                continue
+
+            char_int = int(pieces[0], 16)
+            status = pieces[1].strip()
+            assert status in ('C', 'F', 'S', 'T')
+            case_folding[status][char_int] = [int(c, 16) for c in pieces[2].split()]
+
+        return case_folding
+
+    def _load_special_casing(self):
+        # The unconditional full lowercase mappings, and the Final_Sigma
+        # ones. The language-specific mappings are ignored.
+        unconditional = {}
+        final_sigma = {}
+
+        for line in self._fetch("SpecialCasing.txt").splitlines():
+            (data, _, _) = line.partition("#")
+            pieces = [p.strip() for p in data.split(";")]
+            if len(pieces) < 5:
+                continue
+
+            char_int = int(pieces[0], 16)
+            lower = [int(c, 16) for c in pieces[1].split()]
+            condition = pieces[4]
+            if not condition:
+                unconditional[char_int] = lower
+            elif condition == 'Final_Sigma':
+                final_sigma[char_int] = lower
+
+        return unconditional, final_sigma
+
+    def _load_idna_mapping(self):
+        mapping = collections.defaultdict(list)
+
+        for line in self._fetch("IdnaMappingTable.txt", IDNA_URL).splitlines():
+            (data, _, _) = line.partition("#")
+            pieces = [p.strip() for p in data.split(";")]
+            if len(pieces) < 2:
+                continue
+
+            (low, _, high) = pieces[0].partition("..")
+            low = int(low, 16)
+            high = int(high, 16) if high else low
+
+            # Since Unicode 15.1 the STD3 statuses are folded into valid and
+            # mapped, and UseSTD3ASCIIRules is checked separately.
+            status = pieces[1]
+            assert status in ('valid', 'ignored', 'mapped', 'deviation', 'disallowed')
+            chars = None
+            if status in ('mapped', 'deviation'):
+                chars = [int(c, 16) for c in pieces[2].split()]
+
+            ranges = mapping[status]
+            if ranges and ranges[-1][1] + 1 == low and ranges[-1][2] == chars:
+                ranges[-1] = (ranges[-1][0], high, chars)
+            else:
+                ranges.append((low, high, chars))
+
+        return mapping
+
+    def _load_derived_ranges(self, filename, values=None, base_url=UCD_URL):
+        ranges = []
+
+        for line in self._fetch(filename, base_url).splitlines():
+            (data, _, _) = line.partition("#")
+            pieces = [p.strip() for p in data.split(";")]
+            if len(pieces) != 2 or (values is not None and pieces[1] not in values):
+                continue
+
+            (low, _, high) = pieces[0].partition("..")
+            low = int(low, 16)
+            high = int(high, 16) if high else low
+            ranges.append((low, high, pieces[1]))
+
+        ranges.sort()
+        merged = []
+        for (low, high, value) in ranges:
+            if merged and merged[-1][1] + 1 == low and merged[-1][2] == value:
+                merged[-1] = (merged[-1][0], high, value)
+            else:
+                merged.append((low, high, value))
+        return merged
+
+    def _load_bidi_classes(self):
+        # Left_To_Right is the default, so it isn't stored.
+        return self._load_derived_ranges("extracted/DerivedBidiClass.txt", BIDI_CLASSES)
+
+    def _load_joining_types(self):
+        # Only the joining types used by the CONTEXTJ rule of RFC 5892 are
+        # stored: Join_Causing and Non_Joining characters never match it.
+        return self._load_derived_ranges("extracted/DerivedJoiningType.txt", JOINING_TYPES)
+
+    def _load_script_extensions(self):
+        # ScriptExtensions.txt uses the short names of the scripts.
+        long_names = {}
+        for line in self._fetch("PropertyValueAliases.txt").splitlines():
+            (data, _, _) = line.partition("#")
+            pieces = [p.strip() for p in data.split(";")]
+            if len(pieces) >= 3 and pieces[0] == 'sc':
+                long_names[pieces[1]] = pieces[2]
+
+        extensions = []
+        for line in self._fetch("ScriptExtensions.txt").splitlines():
+            (data, _, _) = line.partition("#")
+            pieces = [p.strip() for p in data.split(";")]
+            if len(pieces) != 2:
+                continue
+
+            (low, _, high) = pieces[0].partition("..")
+            extensions.append((int(low, 16), int(high or low, 16),
+                               [long_names[s] for s in pieces[1].split()]))
+        extensions.sort()
+        return extensions
+
+    def _load_confusables(self):
+        confusables = {}
+        for line in self._fetch("confusables.txt", SECURITY_URL).splitlines():
+            (data, _, _) = line.partition("#")
+            pieces = [p.strip() for p in data.split(";")]
+            if len(pieces) != 3:
+                continue
+
+            source = [int(c, 16) for c in pieces[0].split()]
+            assert len(source) == 1
+            confusables[source[0]] = [int(c, 16) for c in pieces[1].split()]
+        return confusables
+
+    def _load_stringprep_tables(self):
+        tables = {}
+        table = None
+        for line in self._fetch("rfc3454.txt", RFC_URL).splitlines():
+            line = line.strip()
+            if line.startswith("----- Start Table "):
+                table = line[len("----- Start Table "):-len(" -----")]
+                tables[table] = []
+                continue
+            if line.startswith("----- End Table "):
+                table = None
+                continue
+            # Skip the page headers and footers inside the tables.
+            if table not in STRINGPREP_TABLES or not line or "Standards Track" in line \
+                    or line.startswith("RFC 3454") or line.startswith("\f"):
+                continue
+
+            pieces = [p.strip() for p in line.split(";")]
+            (low, _, high) = pieces[0].partition("-")
+            if table.startswith("B."):
+                tables[table].append((int(low, 16), [int(c, 16) for c in pieces[1].split()]))
+            else:
+                tables[table].append((int(low, 16), int(high or low, 16)))
+        return tables
+
+    def _load_normalization_corrections(self):
+        # stringprep normalizes with the decompositions of Unicode 3.2, so the
+        # corrections made after it have to be reverted.
+        corrections = {}
+        for line in self._fetch("NormalizationCorrections.txt").splitlines():
+            (data, _, _) = line.partition("#")
+            pieces = [p.strip() for p in data.split(";")]
+            if len(pieces) < 4:
+                continue
+            version = tuple(int(v) for v in pieces[3].split("."))
+            if version > (3, 2, 0):
+                corrections[int(pieces[0], 16)] = int(pieces[1], 16)
+        return corrections
+
     def _load_norm_tests(self):
         tests = []
         for line in self._fetch("NormalizationTest.txt").splitlines():
@@ -208,6 +518,42 @@ class UnicodeData(object):
 
         return tests
 
+    def _load_idna_tests(self):
+        def unescape(field):
+            if field == '""':
+                return ""
+            field = re.sub(r"\\x\{([0-9A-Fa-f]+)\}", lambda m: chr(int(m.group(1), 16)), field)
+            return re.sub(r"\\u([0-9A-Fa-f]{4})", lambda m: chr(int(m.group(1), 16)), field)
+
+        def status(field):
+            return [code.strip() for code in field.strip("[]").split(",") if code.strip()]
+
+        tests = []
+        for line in self._fetch("IdnaTestV2.txt", IDNA_URL).splitlines():
+            (test_data, _, _) = line.partition("#")
+            pieces = [p.strip() for p in test_data.split(";")]
+            if len(pieces) < 7:
+                continue
+
+            # Blank columns default to the ones before them, as described in
+            # the header of the file.
+            source = unescape(pieces[0])
+            to_unicode = unescape(pieces[1]) if pieces[1] else source
+            to_unicode_status = status(pieces[2])
+            to_ascii_n = unescape(pieces[3]) if pieces[3] else to_unicode
+            to_ascii_n_status = status(pieces[4]) if pieces[4] else to_unicode_status
+            to_ascii_t = unescape(pieces[5]) if pieces[5] else to_ascii_n
+            to_ascii_t_status = status(pieces[6]) if pieces[6] else to_ascii_n_status
+
+            # Unpaired surrogates can't be represented in a `str`.
+            if any(0xD800 <= ord(c) <= 0xDFFF for c in source + to_unicode):
+                continue
+
+            tests.append(IdnaTest(source, to_unicode, to_unicode_status, to_ascii_n,
+                                  to_ascii_n_status, to_ascii_t, to_ascii_t_status))
+
+        return tests
+
     def _compute_canonical_comp(self):
         canon_comp = {}
         comp_exclusions = [
@@ -302,6 +648,53 @@ class UnicodeData(object):
 
         return canon_fully_decomp, compat_fully_decomp
 
+    def _compute_precis_properties(self):
+        # The derived property of RFC 8264 section 8, from the categories of
+        # section 9, in ranges. UNASSIGNED is the default, so it isn't stored.
+        def expand(ranges):
+            return set(c for r in ranges for c in range(r[0], r[1] + 1))
+
+        join_control = expand(self.prop_list['Join_Control'])
+        noncharacters = expand(self.prop_list['Noncharacter_Code_Point'])
+        ignorables = expand(self.core_props['Default_Ignorable_Code_Point'])
+        old_hangul_jamo = expand(self.hangul_syllable_types)
+        # A single code point changes under NFKC exactly when NFKC_QC=No.
+        has_compat = expand(
+            (int(low, 16), int(high or low, 16))
+            for (low, high, data) in self.norm_props['NFKC_QC'] if data == 'N')
+
+        properties = []
+        for char_int in range(0x110000):
+            category = self.general_category.get(char_int, 'Cn')
+            if char_int in PRECIS_EXCEPTIONS:
+                prop = PRECIS_EXCEPTIONS[char_int]
+            elif category == 'Cn' and char_int not in noncharacters:
+                prop = None
+            elif 0x21 <= char_int <= 0x7E:
+                prop = 'PValid'
+            elif char_int in join_control:
+                prop = 'ContextJ'
+            elif char_int in old_hangul_jamo or char_int in ignorables or char_int in noncharacters:
+                prop = 'Disallowed'
+            elif category == 'Cc':
+                prop = 'Disallowed'
+            elif char_int in has_compat:
+                prop = 'FreePVal'
+            elif category in ('Ll', 'Lu', 'Lo', 'Nd', 'Lm', 'Mn', 'Mc'):
+                prop = 'PValid'
+            elif category in ('Lt', 'Nl', 'No', 'Me', 'Zs', 'Sm', 'Sc', 'Sk', 'So',
+                              'Pc', 'Pd', 'Ps', 'Pe', 'Pi', 'Pf', 'Po'):
+                prop = 'FreePVal'
+            else:
+                prop = 'Disallowed'
+
+            if properties and properties[-1][1] + 1 == char_int and properties[-1][2] == prop:
+                properties[-1] = (properties[-1][0], char_int, prop)
+            else:
+                properties.append((char_int, char_int, prop))
+
+        return [r for r in properties if r[2] is not None]
+
     def _compute_stream_safe_tables(self):
         """
         To make a text stream-safe with the Stream-Safe Text Process (UAX15-D4),
@@ -455,6 +848,327 @@ def gen_nfkd_qc(prop_tables, out):
     gen_qc_match(prop_tables['NFKD_QC'], out)
     out.write("}\n")
 
+def gen_nfkc_casefold(prop_tables, out):
+    mapped = {}
+    removed = []
+    for low, high, data in prop_tables['NFKC_CF']:
+        low = int(low, 16)
+        high = int(high, 16) if high else low
+        if data:
+            for char_int in range(low, high + 1):
+                mapped[char_int] = [int(c, 16) for c in data.split()]
+        elif removed and removed[-1][1] + 1 == low:
+            removed[-1] = (removed[-1][0], high)
+        else:
+            removed.append((low, high))
+
+    # Changes_When_NFKC_Casefolded is exactly the set of characters with a
+    # mapping, so the table doubles as its quick check.
+    changes = set()
+    for low, high, _ in prop_tables['Changes_When_NFKC_Casefolded']:
+        low = int(low, 16)
+        high = int(high, 16) if high else low
+        changes.update(range(low, high + 1))
+    assert changes == set(mapped) | set(c for low, high in removed for c in range(low, high + 1))
+
+    offsets = {}
+    offset = 0
+    out.write("pub(crate) const NFKC_CASEFOLD_CHARS: &[char] = &[\n")
+    for k, v in mapped.items():
+        offsets[k] = offset
+        offset += len(v)
+        for c in v:
+            out.write("    '\\u{%s}',\n" % hexify(c))
+    assert offset < 65536
+    out.write("];\n")
+    gen_mph_data('nfkc_casefold', mapped, "(u32, (u16, u16))",
+        lambda k: f"(0x{k:05X}, (0x{offsets[k]:03X}, 0x{len(mapped[k]):X}))", 1)
+
+    # The characters mapped to nothing are few ranges of default ignorables,
+    # one of which spans a whole block of mostly unassigned code points.
+    gen_match_fn('is_nfkc_casefold_removed', removed, out)
+
+def gen_case_folding(case_folding, out):
+    # Simple case folding: the common (C) and simple (S) mappings, which all
+    # map to a single character.
+    simple = {}
+    for status in ('C', 'S'):
+        for k, v in case_folding[status].items():
+            assert len(v) == 1 and k not in simple
+            simple[k] = v[0]
+    gen_mph_data('case_folding_simple', simple, '(u32, char)',
+        lambda k: f"(0x{k:05X}, '\\u{{{simple[k]:04X}}}')", 1)
+
+    # Full case folding (F) only differs from simple case folding for a few
+    # characters, so it's stored separately and falls back to the common
+    # mappings.
+    full = case_folding['F']
+    offsets = {}
+    offset = 0
+    out.write("pub(crate) const CASE_FOLDING_FULL_CHARS: &[char] = &[\n")
+    for k, v in full.items():
+        offsets[k] = offset
+        offset += len(v)
+        for c in v:
+            out.write("    '\\u{%s}',\n" % hexify(c))
+    out.write("];\n")
+    gen_mph_data('case_folding_full', full, "(u32, (u16, u16))",
+        lambda k: f"(0x{k:05X}, (0x{offsets[k]:03X}, 0x{len(full[k]):X}))", 1)
+
+    # The Turkic mappings (T) are few enough for a match.
+    out.write("\n#[inline]\n")
+    out.write("pub fn case_folding_turkic(c: char) -> Option<char> {\n")
+    out.write("    match c {\n")
+    for k, v in sorted(case_folding['T'].items()):
+        assert len(v) == 1
+        out.write("        '\\u{%s}' => Some('\\u{%s}'),\n" % (hexify(k), hexify(v[0])))
+    out.write("        _ => None,\n")
+    out.write("    }\n")
+    out.write("}\n")
+
+def gen_lowercase(lowercase, special_casing, core_props, out):
+    # The simple lowercase mappings of UnicodeData.txt all map to a single
+    # character.
+    gen_mph_data('lowercase', lowercase, '(u32, char)',
+        lambda k: f"(0x{k:05X}, '\\u{{{lowercase[k]:04X}}}')", 1)
+
+    # SpecialCasing.txt only adds a few longer mappings unconditionally, and
+    # the final form of sigma.
+    unconditional, final_sigma = special_casing
+    special = {k: v for k, v in unconditional.items() if v != [lowercase.get(k, k)]}
+    assert all(len(v) > 1 for v in special.values())
+    out.write("\n#[inline]\n")
+    out.write("pub fn lowercase_special(c: char) -> Option<&'static [char]> {\n")
+    out.write("    match c {\n")
+    for k, v in sorted(special.items()):
+        out.write("        '\\u{%s}' => Some(&[%s]),\n" % (
+            hexify(k), ", ".join("'\\u{%s}'" % hexify(c) for c in v)))
+    out.write("        _ => None,\n")
+    out.write("    }\n")
+    out.write("}\n")
+
+    out.write("\n#[inline]\n")
+    out.write("pub fn lowercase_final_sigma(c: char) -> Option<char> {\n")
+    out.write("    match c {\n")
+    for k, v in sorted(final_sigma.items()):
+        assert len(v) == 1
+        out.write("        '\\u{%s}' => Some('\\u{%s}'),\n" % (hexify(k), hexify(v[0])))
+    out.write("        _ => None,\n")
+    out.write("    }\n")
+    out.write("}\n")
+
+    # The properties which the Final_Sigma condition depends on.
+    out.write("\n")
+    gen_range_table('cased', core_props['Cased'], out)
+    out.write("\n")
+    gen_range_table('case_ignorable', core_props['Case_Ignorable'], out)
+
+def gen_range_table(name, ranges, out):
+    out.write("pub(crate) const %s: &[(char, char)] = &[\n" % name.upper())
+    for (low, high) in ranges:
+        out.write("    ('\\u{%s}', '\\u{%s}'),\n" % (hexify(low), hexify(high)))
+    out.write("];\n")
+
+def gen_xid(core_props, out):
+    # XID_Start and XID_Continue are closed under NFKC (see UAX #31), so
+    # normalizing an identifier never makes it invalid.
+    out.write("\n")
+    gen_range_table('xid_start', core_props['XID_Start'], out)
+    out.write("\n")
+    gen_range_table('xid_continue', core_props['XID_Continue'], out)
+
+def gen_idna(idna_mapping, bidi_classes, joining_types, out):
+    mapped = {}
+    for (low, high, chars) in idna_mapping['mapped']:
+        for char_int in range(low, high + 1):
+            mapped[char_int] = chars
+
+    offsets = {}
+    offset = 0
+    out.write("\npub(crate) const IDNA_MAPPED_CHARS: &[char] = &[\n")
+    for k, v in mapped.items():
+        offsets[k] = offset
+        offset += len(v)
+        for c in v:
+            out.write("    '\\u{%s}',\n" % hexify(c))
+    assert offset < 65536
+    out.write("];\n")
+    gen_mph_data('idna_mapped', mapped, "(u32, (u16, u16))",
+        lambda k: f"(0x{k:05X}, (0x{offsets[k]:04X}, 0x{len(mapped[k]):X}))", 1)
+
+    out.write("\n")
+    gen_range_table('idna_valid', [(low, high) for (low, high, _) in idna_mapping['valid']], out)
+
+    gen_match_fn('is_idna_ignored', [(low, high) for (low, high, _) in idna_mapping['ignored']], out)
+
+    # The deviations are the few characters which IDNA2003 and IDNA2008
+    # treat differently, mapped only by Transitional Processing.
+    out.write("\n#[inline]\n")
+    out.write("pub fn idna_deviation(c: char) -> Option<&'static [char]> {\n")
+    out.write("    match c {\n")
+    for (low, high, chars) in idna_mapping['deviation']:
+        for char_int in range(low, high + 1):
+            out.write("        '\\u{%s}' => Some(&[%s]),\n" % (
+                hexify(char_int), ", ".join("'\\u{%s}'" % hexify(c) for c in chars)))
+    out.write("        _ => None,\n")
+    out.write("    }\n")
+    out.write("}\n")
+
+    out.write("\npub(crate) const BIDI_CLASS: &[(char, char, BidiClass)] = &[\n")
+    for (low, high, value) in bidi_classes:
+        out.write("    ('\\u{%s}', '\\u{%s}', BidiClass::%s),\n" % (hexify(low), hexify(high), value))
+    out.write("];\n")
+
+    out.write("\npub(crate) const JOINING_TYPE: &[(char, char, JoiningType)] = &[\n")
+    for (low, high, value) in joining_types:
+        out.write("    ('\\u{%s}', '\\u{%s}', JoiningType::%s),\n" % (hexify(low), hexify(high), JOINING_TYPES[value]))
+    out.write("];\n")
+
+def gen_precis(precis_properties, general_category, width_decomp, out):
+    out.write("\npub(crate) const PRECIS_PROPERTY: &[(char, char, DerivedProperty)] = &[\n")
+    for (low, high, value) in precis_properties:
+        # Surrogates aren't chars, so split the range around them.
+        if low < 0xD800 and high > 0xDFFF:
+            out.write("    ('\\u{%s}', '\\u{%s}', DerivedProperty::%s),\n" % (hexify(low), hexify(0xD7FF), value))
+            low = 0xE000
+        elif 0xD800 <= low <= 0xDFFF:
+            low = 0xE000
+        if 0xD800 <= high <= 0xDFFF:
+            high = 0xD7FF
+        if low > high:
+            continue
+        out.write("    ('\\u{%s}', '\\u{%s}', DerivedProperty::%s),\n" % (hexify(low), hexify(high), value))
+    out.write("];\n")
+
+    gen_mph_data('width_mapping', width_decomp, '(u32, char)',
+        lambda k: f"(0x{k:05X}, '\\u{{{width_decomp[k]:04X}}}')", 1)
+
+    spaces = sorted(c for (c, category) in general_category.items() if category == 'Zs')
+    gen_match_fn('is_space_separator', [(c, c) for c in spaces], out)
+
+def gen_match_fn(name, ranges, out):
+    out.write("\n#[inline]\n")
+    out.write("pub fn %s(c: char) -> bool {\n" % name)
+    out.write("    match c {\n")
+    for i, (low, high) in enumerate(ranges):
+        out.write("        " if i == 0 else "        | ")
+        if low == high:
+            out.write("'\\u{%s}'" % hexify(low))
+        else:
+            out.write("'\\u{%s}'..='\\u{%s}'" % (hexify(low), hexify(high)))
+        out.write(" => true,\n" if i == len(ranges) - 1 else "\n")
+    out.write("        _ => false,\n")
+    out.write("    }\n")
+    out.write("}\n")
+
+def gen_stringprep(tables, corrections, out):
+    out.write("\n")
+    gen_range_table('stringprep_unassigned', tables['A.1'], out)
+
+    nothing = []
+    for (c, _) in tables['B.1']:
+        if nothing and nothing[-1][1] == c - 1:
+            nothing[-1] = (nothing[-1][0], c)
+        else:
+            nothing.append((c, c))
+    gen_match_fn('is_stringprep_mapped_to_nothing', nothing, out)
+
+    case_fold = dict(tables['B.2'])
+    offsets = {}
+    offset = 0
+    out.write("\npub(crate) const STRINGPREP_CASE_FOLD_CHARS: &[char] = &[\n")
+    for k, v in case_fold.items():
+        offsets[k] = offset
+        offset += len(v)
+        for c in v:
+            out.write("    '\\u{%s}',\n" % hexify(c))
+    assert offset < 65536
+    out.write("];\n")
+    gen_mph_data('stringprep_case_fold', case_fold, "(u32, (u16, u16))",
+        lambda k: f"(0x{k:05X}, (0x{offsets[k]:04X}, 0x{len(case_fold[k]):X}))", 1)
+
+    gen_match_fn('is_stringprep_non_ascii_space', tables['C.1.2'], out)
+
+    # The tables prohibited by both Nameprep and SASLprep are merged, apart
+    # from the non-ASCII spaces which SASLprep maps. The surrogate codes of
+    # table C.5 can't occur in a str.
+    prohibited = sorted(r for t in ('C.2.2', 'C.3', 'C.4', 'C.6', 'C.7', 'C.8', 'C.9') for r in tables[t])
+    merged = []
+    for (low, high) in prohibited:
+        if merged and low <= merged[-1][1] + 1:
+            merged[-1] = (merged[-1][0], max(high, merged[-1][1]))
+        else:
+            merged.append((low, high))
+    out.write("\n")
+    gen_range_table('stringprep_prohibited', merged, out)
+
+    out.write("\n")
+    gen_range_table('stringprep_rand_al_cat', tables['D.1'], out)
+    out.write("\n")
+    gen_range_table('stringprep_l_cat', tables['D.2'], out)
+
+    out.write("\n#[inline]\n")
+    out.write("pub fn stringprep_normalization_correction(c: char) -> Option<char> {\n")
+    out.write("    match c {\n")
+    for (k, v) in sorted(corrections.items()):
+        out.write("        '\\u{%s}' => Some('\\u{%s}'),\n" % (hexify(k), hexify(v)))
+    out.write("        _ => None,\n")
+    out.write("    }\n")
+    out.write("}\n")
+
+def gen_security(scripts, script_extensions, identifier_status, confusables, core_props, out):
+    names = sorted(set(name for (_, _, name) in scripts) | {'Unknown'}) + list(AUGMENTED_SCRIPTS)
+    index = {name: i for (i, name) in enumerate(names)}
+    assert len(names) <= 256
+    words = (len(names) + 63) // 64
+
+    def bits(script_names):
+        mask = 0
+        for name in script_names:
+            mask |= 1 << index[name]
+        return ", ".join("0x%016X" % ((mask >> (64 * i)) & (2 ** 64 - 1)) for i in range(words))
+
+    out.write("\npub(crate) const SCRIPT_WORDS: usize = %d;\n" % words)
+    for name in NAMED_SCRIPTS + AUGMENTED_SCRIPTS:
+        out.write("pub(crate) const SCRIPT_%s: u8 = %d;\n" % (name.upper(), index[name]))
+
+    out.write("\npub(crate) const SCRIPT: &[(char, char, u8)] = &[\n")
+    for (low, high, name) in scripts:
+        out.write("    ('\\u{%s}', '\\u{%s}', %d),\n" % (hexify(low), hexify(high), index[name]))
+    out.write("];\n")
+
+    out.write("\npub(crate) const SCRIPT_EXTENSIONS: &[(char, char, [u64; SCRIPT_WORDS])] = &[\n")
+    for (low, high, extension) in script_extensions:
+        out.write("    (\n")
+        out.write("        '\\u{%s}',\n" % hexify(low))
+        out.write("        '\\u{%s}',\n" % hexify(high))
+        out.write("        [%s],\n" % bits(extension))
+        out.write("    ),\n")
+    out.write("];\n")
+
+    recommended = set(RECOMMENDED_SCRIPTS)
+    out.write("\npub(crate) const RECOMMENDED_SCRIPTS: [u64; SCRIPT_WORDS] =\n")
+    out.write("    [%s];\n" % bits(recommended))
+
+    out.write("\n")
+    gen_range_table('identifier_allowed', [(low, high) for (low, high, _) in identifier_status], out)
+
+    offsets = {}
+    offset = 0
+    out.write("\npub(crate) const CONFUSABLE_PROTOTYPE_CHARS: &[char] = &[\n")
+    for k, v in confusables.items():
+        offsets[k] = offset
+        offset += len(v)
+        for c in v:
+            out.write("    '\\u{%s}',\n" % hexify(c))
+    assert offset < 65536
+    out.write("];\n")
+    gen_mph_data('confusable_prototype', confusables, "(u32, (u16, u16))",
+        lambda k: f"(0x{k:05X}, (0x{offsets[k]:04X}, 0x{len(confusables[k]):X}))", 1)
+
+    out.write("\n")
+    gen_range_table('default_ignorable', core_props['Default_Ignorable_Code_Point'], out)
+
 def gen_combining_mark(general_category_mark, out):
     gen_mph_data('combining_mark', general_category_mark, 'u32',
         lambda k: '0x{:05X}'.format(k), 10)
@@ -524,6 +1238,38 @@ pub struct NormalizationTest {
 
     out.write("];\n")
 
+def gen_idna_tests(tests, out):
+    out.write("""#[derive(Debug)]
+pub struct IdnaTest {
+    pub source: &'static str,
+    pub to_unicode: &'static str,
+    pub to_unicode_status: &'static [&'static str],
+    pub to_ascii_n: &'static str,
+    pub to_ascii_n_status: &'static [&'static str],
+    pub to_ascii_t: &'static str,
+    pub to_ascii_t_status: &'static [&'static str],
+}
+
+""")
+
+    out.write("pub const IDNA_TESTS: &[IdnaTest] = &[\n")
+    str_literal = lambda s: '"%s"' % "".join(
+        c if " " <= c <= "~" and c not in '"\\' else "\\u{%04X}" % ord(c) for c in s)
+    status_literal = lambda codes: "&[%s]" % ", ".join('"%s"' % c for c in codes)
+
+    for test in tests:
+        out.write("    IdnaTest {\n")
+        out.write("        source: %s,\n" % str_literal(test.source))
+        out.write("        to_unicode: %s,\n" % str_literal(test.to_unicode))
+        out.write("        to_unicode_status: %s,\n" % status_literal(test.to_unicode_status))
+        out.write("        to_ascii_n: %s,\n" % str_literal(test.to_ascii_n))
+        out.write("        to_ascii_n_status: %s,\n" % status_literal(test.to_ascii_n_status))
+        out.write("        to_ascii_t: %s,\n" % str_literal(test.to_ascii_t))
+        out.write("        to_ascii_t_status: %s,\n" % status_literal(test.to_ascii_t_status))
+        out.write("    },\n")
+
+    out.write("];\n")
+
 # Guaranteed to be less than n.
 def my_hash(x, salt, n):
     # This is hash based on the theory that multiplication is efficient
@@ -584,6 +1330,7 @@ if __name__ == '__main__':
     data = UnicodeData()
     with open("tables.rs", "w", newline = "\n") as out:
         out.write(PREAMBLE)
+        out.write("use crate::properties::{BidiClass, DerivedProperty, JoiningType};\n")
         out.write("use crate::quick_check::IsNormalized;\n")
         out.write("use crate::quick_check::IsNormalized::*;\n")
         out.write("\n")
@@ -612,6 +1359,27 @@ if __name__ == '__main__':
 
         gen_stream_safe(data.ss_leading, data.ss_trailing, out)
 
+        gen_nfkc_casefold(data.norm_props, out)
+
+        gen_case_folding(data.case_folding, out)
+
+        gen_xid(data.core_props, out)
+
+        gen_idna(data.idna_mapping, data.bidi_classes, data.joining_types, out)
+
+        gen_precis(data.precis_properties, data.general_category, data.width_decomp, out)
+
+        gen_lowercase(data.lowercase, data.special_casing, data.core_props, out)
+
+        gen_stringprep(data.stringprep_tables, data.normalization_corrections, out)
+
+        gen_security(data.scripts, data.script_extensions, data.identifier_status, data.confusables,
+                     data.core_props, out)
+
     with open("normalization_tests.rs", "w", newline = "\n") as out:
         out.write(PREAMBLE)
         gen_tests(data.norm_tests, out)
+
+    with open("idna_tests.rs", "w", newline = "\n") as out:
+        out.write(PREAMBLE)
+        gen_idna_tests(data.idna_tests, out)
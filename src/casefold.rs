@@ -0,0 +1,275 @@
+// Copyright 2012-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Case folding and caseless matching, as described in section 3.13 of the
+//! Unicode Standard.
+
+use crate::decompose::Decompositions;
+use crate::lookups::{case_folding_full, case_folding_simple};
+use crate::tables::case_folding_turkic;
+use core::{
+    fmt::{self, Write},
+    iter::FusedIterator,
+    str::Chars,
+};
+
+/// The case folding mappings from `CaseFolding.txt` to apply.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CaseFolding {
+    /// Full case folding (statuses C and F), which may map a character to
+    /// several, such as `ß` to `ss`. This is the folding used by `toCasefold`.
+    Full,
+    /// Simple case folding (statuses C and S), which always maps a character
+    /// to a single one.
+    Simple,
+    /// Full case folding with the Turkic mappings (status T) for dotted and
+    /// dotless I.
+    FullTurkic,
+    /// Simple case folding with the Turkic mappings (status T) for dotted and
+    /// dotless I.
+    SimpleTurkic,
+}
+
+impl CaseFolding {
+    /// Return whether the two strings are equal under canonical caseless
+    /// matching with this folding, that is `NFD(fold(NFD(a))) == NFD(fold(NFD(b)))`.
+    pub fn canonical_caseless_eq(self, a: &str, b: &str) -> bool {
+        a == b || self.canonical_caseless(a).eq(self.canonical_caseless(b))
+    }
+
+    /// Return whether the two strings are equal under compatibility caseless
+    /// matching with this folding, that is
+    /// `NFKD(fold(NFKD(fold(NFD(a))))) == NFKD(fold(NFKD(fold(NFD(b)))))`.
+    pub fn compatibility_caseless_eq(self, a: &str, b: &str) -> bool {
+        a == b
+            || self
+                .compatibility_caseless(a)
+                .eq(self.compatibility_caseless(b))
+    }
+
+    #[inline]
+    fn canonical_caseless(self, s: &str) -> Decompositions<CaseFolds<Decompositions<Chars<'_>>>> {
+        Decompositions::new_canonical(CaseFolds::new(
+            Decompositions::new_canonical(s.chars()),
+            self,
+        ))
+    }
+
+    #[inline]
+    #[allow(clippy::type_complexity)]
+    fn compatibility_caseless(
+        self,
+        s: &str,
+    ) -> Decompositions<CaseFolds<Decompositions<CaseFolds<Decompositions<Chars<'_>>>>>> {
+        Decompositions::new_compatible(CaseFolds::new(
+            Decompositions::new_compatible(CaseFolds::new(
+                Decompositions::new_canonical(s.chars()),
+                self,
+            )),
+            self,
+        ))
+    }
+
+    #[inline]
+    pub(crate) fn fold(self, c: char) -> Folded {
+        if c.is_ascii() {
+            return match c {
+                'I' if self.is_turkic() => Folded::One('\u{131}'),
+                _ => Folded::One(c.to_ascii_lowercase()),
+            };
+        }
+        if self.is_turkic() {
+            if let Some(f) = case_folding_turkic(c) {
+                return Folded::One(f);
+            }
+        }
+        if self.is_full() {
+            if let Some(f) = case_folding_full(c) {
+                return Folded::Many(f);
+            }
+        }
+        Folded::One(case_folding_simple(c).unwrap_or(c))
+    }
+
+    #[inline]
+    fn is_full(self) -> bool {
+        match self {
+            CaseFolding::Full | CaseFolding::FullTurkic => true,
+            CaseFolding::Simple | CaseFolding::SimpleTurkic => false,
+        }
+    }
+
+    #[inline]
+    fn is_turkic(self) -> bool {
+        match self {
+            CaseFolding::FullTurkic | CaseFolding::SimpleTurkic => true,
+            CaseFolding::Full | CaseFolding::Simple => false,
+        }
+    }
+}
+
+pub(crate) enum Folded {
+    One(char),
+    Many(&'static [char]),
+}
+
+/// Return whether the two strings are equal under canonical caseless matching
+/// (definition D145 of the Unicode Standard) with full case folding.
+///
+/// ```rust
+/// use unicode_normalization::canonical_caseless_eq;
+///
+/// assert!(canonical_caseless_eq("Stra\u{df}e", "STRASSE"));
+/// assert!(canonical_caseless_eq("\u{212b}", "a\u{30a}"));
+/// assert!(!canonical_caseless_eq("\u{2460}", "1"));
+/// ```
+#[inline]
+pub fn canonical_caseless_eq(a: &str, b: &str) -> bool {
+    CaseFolding::Full.canonical_caseless_eq(a, b)
+}
+
+/// Return whether the two strings are equal under compatibility caseless
+/// matching (definition D146 of the Unicode Standard) with full case folding.
+///
+/// ```rust
+/// use unicode_normalization::compatibility_caseless_eq;
+///
+/// assert!(compatibility_caseless_eq("\u{2460}", "1"));
+/// assert!(compatibility_caseless_eq("\u{3392}", "mhz"));
+/// ```
+#[inline]
+pub fn compatibility_caseless_eq(a: &str, b: &str) -> bool {
+    CaseFolding::Full.compatibility_caseless_eq(a, b)
+}
+
+/// External iterator for a string's case folding.
+///
+/// Case folding doesn't preserve normalization, so the result should usually
+/// be normalized again; see [`canonical_caseless_eq`] for an example.
+#[derive(Clone)]
+pub struct CaseFolds<I> {
+    iter: I,
+    folding: CaseFolding,
+    // The rest of a full folding to more than one character.
+    pending: &'static [char],
+}
+
+impl<I: Iterator<Item = char>> CaseFolds<I> {
+    /// Create a new iterator that applies the given case folding.
+    #[inline]
+    pub fn new(iter: I, folding: CaseFolding) -> CaseFolds<I> {
+        CaseFolds {
+            iter,
+            folding,
+            pending: &[],
+        }
+    }
+}
+
+impl<I: Iterator<Item = char>> Iterator for CaseFolds<I> {
+    type Item = char;
+
+    #[inline]
+    fn next(&mut self) -> Option<char> {
+        if let Some((&c, rest)) = self.pending.split_first() {
+            self.pending = rest;
+            return Some(c);
+        }
+
+        match self.folding.fold(self.iter.next()?) {
+            Folded::One(c) => Some(c),
+            Folded::Many(s) => {
+                self.pending = &s[1..];
+                Some(s[0])
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (lower, _) = self.iter.size_hint();
+        (lower + self.pending.len(), None)
+    }
+}
+
+impl<I: Iterator<Item = char> + FusedIterator> FusedIterator for CaseFolds<I> {}
+
+impl<I: Iterator<Item = char> + Clone> fmt::Display for CaseFolds<I> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for c in self.clone() {
+            f.write_char(c)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{canonical_caseless_eq, compatibility_caseless_eq, CaseFolding, CaseFolds};
+
+    #[cfg(not(feature = "std"))]
+    use alloc::string::String;
+
+    fn fold(s: &str, folding: CaseFolding) -> String {
+        CaseFolds::new(s.chars(), folding).collect()
+    }
+
+    #[test]
+    fn test_case_folds() {
+        assert_eq!(fold("Hello, World", CaseFolding::Full), "hello, world");
+        assert_eq!(fold("Stra\u{df}e", CaseFolding::Full), "strasse");
+        assert_eq!(fold("Stra\u{df}e", CaseFolding::Simple), "stra\u{df}e");
+        assert_eq!(fold("\u{1e9e}", CaseFolding::Full), "ss");
+        assert_eq!(fold("\u{1e9e}", CaseFolding::Simple), "\u{df}");
+        assert_eq!(fold("\u{1f88}", CaseFolding::Full), "\u{1f00}\u{3b9}");
+        assert_eq!(fold("\u{1f88}", CaseFolding::Simple), "\u{1f80}");
+        assert_eq!(fold("\u{10400}", CaseFolding::Simple), "\u{10428}");
+        assert_eq!(
+            fold("\u{3a3}\u{3c2}", CaseFolding::Simple),
+            "\u{3c3}\u{3c3}"
+        );
+
+        assert_eq!(fold("I\u{130}", CaseFolding::Full), "ii\u{307}");
+        assert_eq!(fold("I\u{130}", CaseFolding::Simple), "i\u{130}");
+        assert_eq!(fold("I\u{130}", CaseFolding::FullTurkic), "\u{131}i");
+        assert_eq!(fold("I\u{130}", CaseFolding::SimpleTurkic), "\u{131}i");
+    }
+
+    #[test]
+    fn test_canonical_caseless_eq() {
+        assert!(canonical_caseless_eq("", ""));
+        assert!(canonical_caseless_eq("Stra\u{df}e", "STRASSE"));
+        assert!(canonical_caseless_eq("\u{212b}", "\u{e5}"));
+        assert!(canonical_caseless_eq("\u{c5}", "a\u{30a}"));
+        // U+01F0 only folds correctly after its decomposition is folded.
+        assert!(canonical_caseless_eq("\u{1f0}", "J\u{30c}"));
+        // U+0345 folds to U+03B9 but keeps its place after reordering.
+        assert!(canonical_caseless_eq(
+            "\u{3b1}\u{345}\u{301}",
+            "\u{391}\u{301}\u{399}"
+        ));
+        assert!(!canonical_caseless_eq("\u{2460}", "1"));
+        assert!(!canonical_caseless_eq("a", "b"));
+        assert!(!canonical_caseless_eq("ab", "a"));
+
+        assert!(CaseFolding::FullTurkic.canonical_caseless_eq("I", "\u{131}"));
+        assert!(!CaseFolding::Full.canonical_caseless_eq("I", "\u{131}"));
+        assert!(!CaseFolding::Simple.canonical_caseless_eq("\u{df}", "ss"));
+    }
+
+    #[test]
+    fn test_compatibility_caseless_eq() {
+        assert!(compatibility_caseless_eq("\u{2460}", "1"));
+        assert!(compatibility_caseless_eq("\u{3392}", "MHz"));
+        assert!(compatibility_caseless_eq("\u{2126}", "\u{3c9}"));
+        // U+1D2C MODIFIER LETTER CAPITAL A only folds after the first NFKD.
+        assert!(compatibility_caseless_eq("\u{1d2c}", "a"));
+        assert!(!compatibility_caseless_eq("\u{2026}", ".."));
+    }
+}
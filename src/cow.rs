@@ -0,0 +1,160 @@
+// Copyright 2012-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Normalization of string slices that avoids allocating when the input is
+//! already normalized.
+
+use crate::form::Form;
+use crate::quick_check::quick_check_span;
+use tinyvec::TinyVec;
+
+#[cfg(not(feature = "std"))]
+use alloc::{borrow::Cow, string::String};
+#[cfg(feature = "std")]
+use std::borrow::Cow;
+
+#[inline]
+fn normalize_cow(s: &str, form: Form) -> Cow<'_, str> {
+    let boundary = quick_check_span(s, form);
+    if boundary == s.len() {
+        return Cow::Borrowed(s);
+    }
+    normalize_cow_from(s, boundary, form)
+}
+
+/// Normalize `s` from the segment starting at `boundary`, before which it is
+/// known to be normalized.
+fn normalize_cow_from(s: &str, mut boundary: usize, form: Form) -> Cow<'_, str> {
+    // The output is only allocated once a segment turns out to change: the
+    // quick check may stop on a `Maybe`, and the rest of the string can still
+    // be normalized.
+    let mut out: Option<String> = None;
+    let mut segment: TinyVec<[char; 16]> = TinyVec::new();
+    while boundary < s.len() {
+        let end = boundary + form.segment_len(&s[boundary..]);
+        let unnormalized = &s[boundary..end];
+        segment.clear();
+        form.normalize_chars(unnormalized.chars(), |c| segment.push(c));
+        match out {
+            Some(ref mut out) => out.extend(segment.iter()),
+            None if segment.iter().cloned().eq(unnormalized.chars()) => {}
+            None => {
+                let mut owned = String::with_capacity(s.len());
+                owned.push_str(&s[..boundary]);
+                owned.extend(segment.iter());
+                out = Some(owned);
+            }
+        }
+
+        boundary = end + quick_check_span(&s[end..], form);
+        if let Some(ref mut out) = out {
+            out.push_str(&s[end..boundary]);
+        }
+    }
+
+    match out {
+        Some(out) => Cow::Owned(out),
+        None => Cow::Borrowed(s),
+    }
+}
+
+/// Return the string in Unicode Normalization Form C, borrowing it if it is
+/// already normalized.
+///
+/// Otherwise, the longest prefix of `s` that is proven normalized by the
+/// quick check is copied verbatim, and only the remainder is normalized.
+#[inline]
+pub fn nfc_cow(s: &str) -> Cow<'_, str> {
+    normalize_cow(s, Form::Nfc)
+}
+
+/// Return the string in Unicode Normalization Form KC, borrowing it if it is
+/// already normalized.
+///
+/// See [`nfc_cow`] for details.
+#[inline]
+pub fn nfkc_cow(s: &str) -> Cow<'_, str> {
+    normalize_cow(s, Form::Nfkc)
+}
+
+/// Return the string in Unicode Normalization Form D, borrowing it if it is
+/// already normalized.
+///
+/// See [`nfc_cow`] for details.
+#[inline]
+pub fn nfd_cow(s: &str) -> Cow<'_, str> {
+    normalize_cow(s, Form::Nfd)
+}
+
+/// Return the string in Unicode Normalization Form KD, borrowing it if it is
+/// already normalized.
+///
+/// See [`nfc_cow`] for details.
+#[inline]
+pub fn nfkd_cow(s: &str) -> Cow<'_, str> {
+    normalize_cow(s, Form::Nfkd)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{nfc_cow, nfd_cow, nfkc_cow, nfkd_cow};
+
+    #[cfg(not(feature = "std"))]
+    use alloc::borrow::Cow;
+    #[cfg(feature = "std")]
+    use std::borrow::Cow;
+
+    fn is_borrowed(s: Cow<'_, str>) -> bool {
+        match s {
+            Cow::Borrowed(_) => true,
+            Cow::Owned(_) => false,
+        }
+    }
+
+    #[test]
+    fn test_borrowed_when_normalized() {
+        for s in &["", "abc", "Introducci\u{00f3}n", "\u{1e0d}\u{307}", "\u{ac1c}"] {
+            assert!(is_borrowed(nfc_cow(s)));
+            assert!(is_borrowed(nfkc_cow(s)));
+        }
+        for s in &["", "abc", "Introduccio\u{0301}n", "d\u{323}\u{307}"] {
+            assert!(is_borrowed(nfd_cow(s)));
+            assert!(is_borrowed(nfkd_cow(s)));
+        }
+
+        // U+0301 is `Maybe` for NFC, but cannot compose with `b`.
+        assert!(is_borrowed(nfc_cow("ab\u{301}")));
+        assert!(is_borrowed(nfc_cow("b\u{301}c\u{1161}x\u{301}")));
+    }
+
+    #[test]
+    fn test_normalizes_tail() {
+        let c = nfc_cow("Introduccio\u{0301}n a Unicode.pdf");
+        assert_eq!(c, "Introducci\u{00f3}n a Unicode.pdf");
+        assert!(!is_borrowed(c));
+
+        assert_eq!(nfd_cow("abc\u{1e0b}\u{323}"), "abcd\u{323}\u{307}");
+        assert_eq!(nfkc_cow("x\u{1e0b}\u{1c4}"), "x\u{1e0b}D\u{17d}");
+        assert_eq!(nfkd_cow("\u{2026}\u{2026}"), "......");
+
+        // Reordering must go back to the last starter before the violation.
+        assert_eq!(
+            nfc_cow("xa\u{300}\u{305}\u{315}\u{5ae}b"),
+            "x\u{e0}\u{5ae}\u{305}\u{315}b"
+        );
+
+        // Segments which turn out to be normalized are copied around the
+        // ones which change.
+        assert_eq!(
+            nfc_cow("b\u{301}x\u{1100}\u{1161}y\u{301}e\u{301}"),
+            "b\u{301}x\u{ac00}\u{fd}\u{e9}"
+        );
+    }
+}
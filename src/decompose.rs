@@ -12,10 +12,16 @@ use core::iter::{Fuse, FusedIterator};
 use core::ops::Range;
 use tinyvec::TinyVec;
 
+/// The only non-starter whose `NFKC_Casefold` mapping is not its canonical
+/// decomposition.
+pub(crate) const YPOGEGRAMMENI: char = '\u{345}';
+
 #[derive(Clone)]
 enum DecompositionType {
     Canonical,
     Compatible,
+    NfkcCasefold,
+    Hfs,
 }
 
 /// External iterator for a string decomposition's characters.
@@ -64,13 +70,45 @@ impl<I: Iterator<Item = char>> Decompositions<I> {
             ready: 0..0,
         }
     }
+
+    /// Create a new decomposition iterator which applies the `NFKC_Casefold`
+    /// mapping, to be recomposed for NFKC_Casefold.
+    #[inline]
+    pub(crate) fn new_nfkc_casefold(iter: I) -> Decompositions<I> {
+        Decompositions {
+            kind: self::DecompositionType::NfkcCasefold,
+            iter: iter.fuse(),
+            buffer: TinyVec::new(),
+            ready: 0..0,
+        }
+    }
+
+    /// Create a new decomposition iterator for the variant of NFD used by
+    /// HFS+ for file names, which leaves some characters undecomposed and
+    /// follows Unicode 3.2.
+    ///
+    /// Note that this iterator can also be obtained by directly calling [`.hfs_nfd()`](crate::UnicodeNormalization::hfs_nfd)
+    /// on the iterator.
+    #[inline]
+    pub fn new_hfs(iter: I) -> Decompositions<I> {
+        Decompositions {
+            kind: self::DecompositionType::Hfs,
+            iter: iter.fuse(),
+            buffer: TinyVec::new(),
+            ready: 0..0,
+        }
+    }
 }
 
 impl<I> Decompositions<I> {
     #[inline]
     fn push_back(&mut self, ch: char) {
         let class = super::char::canonical_combining_class(ch);
+        self.push_back_with_class(ch, class);
+    }
 
+    #[inline]
+    fn push_back_with_class(&mut self, ch: char, class: u8) {
         if class == 0 {
             self.sort_pending();
             self.buffer.push((class, ch));
@@ -123,6 +161,14 @@ impl<I: Iterator<Item = char>> Iterator for Decompositions<I> {
                 (Some(ch), &DecompositionType::Compatible) => {
                     super::char::decompose_compatible(ch, |d| self.push_back(d));
                 }
+                (Some(ch), &DecompositionType::NfkcCasefold) => {
+                    crate::normalize::decompose_nfkc_casefold(ch, |d| self.push_back(d));
+                }
+                (Some(ch), &DecompositionType::Hfs) => {
+                    crate::normalize::decompose_hfs(ch, |d| {
+                        self.push_back_with_class(d, crate::normalize::hfs_combining_class(d))
+                    });
+                }
                 (None, _) => {
                     if self.buffer.is_empty() {
                         return None;
@@ -152,6 +198,13 @@ impl<I: Iterator<Item = char>> Iterator for Decompositions<I> {
         // case of buffering then unbuffering a single character with each call.
         let (_, ch) = self.buffer[self.ready.start];
         self.increment_next_ready();
+        if let DecompositionType::NfkcCasefold = self.kind {
+            // U+0345 COMBINING GREEK YPOGEGRAMMENI folds to a starter, so it
+            // is only folded after canonical reordering.
+            if ch == YPOGEGRAMMENI {
+                return Some('\u{3B9}');
+            }
+        }
         Some(ch)
     }
 
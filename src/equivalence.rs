@@ -0,0 +1,424 @@
+// Copyright 2012-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Comparison of strings for canonical and compatibility equivalence without
+//! allocating.
+
+use crate::decompose::Decompositions;
+use crate::form::Form;
+use crate::quick_check::quick_check_span;
+use core::borrow::Borrow;
+use core::cmp::Ordering;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::str::Chars;
+
+/// Return whether the two strings are canonically equivalent, that is whether
+/// they have the same NFD (and so the same NFC).
+///
+/// The decompositions of both strings are compared in lockstep, stopping at
+/// the first difference. If both strings are already in NFD, or both are
+/// already in NFC, they are compared directly instead.
+///
+/// ```rust
+/// use unicode_normalization::canonically_equivalent;
+///
+/// assert!(canonically_equivalent("Introducci\u{f3}n", "Introduccio\u{301}n"));
+/// assert!(canonically_equivalent("\u{1e0b}\u{323}", "\u{1e0d}\u{307}"));
+/// assert!(!canonically_equivalent("\u{fb01}", "fi"));
+/// ```
+#[inline]
+pub fn canonically_equivalent(a: &str, b: &str) -> bool {
+    equivalent(a, b, Form::Nfd, Form::Nfc)
+}
+
+/// Return whether the two strings are compatibility equivalent, that is
+/// whether they have the same NFKD (and so the same NFKC).
+///
+/// See [`canonically_equivalent`] for details.
+///
+/// ```rust
+/// use unicode_normalization::compatibility_equivalent;
+///
+/// assert!(compatibility_equivalent("\u{fb01}", "fi"));
+/// assert!(!compatibility_equivalent("\u{2026}", ".."));
+/// ```
+#[inline]
+pub fn compatibility_equivalent(a: &str, b: &str) -> bool {
+    equivalent(a, b, Form::Nfkd, Form::Nfkc)
+}
+
+fn equivalent(a: &str, b: &str, decomposed: Form, composed: Form) -> bool {
+    // Text up to a boundary shared by both strings decomposes the same way in
+    // both, so only what follows it needs to be compared.
+    let start = common_boundary(a, b, decomposed);
+    let (a, b) = (&a[start..], &b[start..]);
+    if a == b {
+        return true;
+    }
+
+    // Distinct strings which are both normalized to the same form can't be
+    // equivalent.
+    for &form in &[decomposed, composed] {
+        if quick_check_span(a, form) == a.len() && quick_check_span(b, form) == b.len() {
+            return false;
+        }
+    }
+
+    decompose(a, decomposed).eq(decompose(b, decomposed))
+}
+
+/// Order the two strings by the code points of their decompositions.
+fn compare(a: &str, b: &str, decomposed: Form) -> Ordering {
+    let start = common_boundary(a, b, decomposed);
+    let (a, b) = (&a[start..], &b[start..]);
+    if a == b {
+        return Ordering::Equal;
+    }
+    decompose(a, decomposed).cmp(decompose(b, decomposed))
+}
+
+#[inline]
+fn decompose(s: &str, decomposed: Form) -> Decompositions<Chars<'_>> {
+    if decomposed == Form::Nfd {
+        Decompositions::new_canonical(s.chars())
+    } else {
+        Decompositions::new_compatible(s.chars())
+    }
+}
+
+/// Return the byte offset of the last normalization boundary within the
+/// common prefix of `a` and `b` which is a boundary in both strings.
+fn common_boundary(a: &str, b: &str, form: Form) -> usize {
+    let prefix = a
+        .bytes()
+        .zip(b.bytes())
+        .position(|(x, y)| x != y)
+        .unwrap_or_else(|| a.len().min(b.len()));
+
+    // Both strings end with the prefix, or continue with different
+    // characters, so check the characters that start at or before it.
+    let mut i = prefix;
+    loop {
+        while !a.is_char_boundary(i) {
+            i -= 1;
+        }
+        if i == 0 || (is_boundary_at(a, i, form) && is_boundary_at(b, i, form)) {
+            return i;
+        }
+        i -= 1;
+    }
+}
+
+#[inline]
+fn is_boundary_at(s: &str, i: usize, form: Form) -> bool {
+    match s[i..].chars().next() {
+        Some(c) => form.is_boundary_before(c),
+        None => true,
+    }
+}
+
+macro_rules! equivalence_key {
+    ($(#[$attr:meta])* $name:ident, $form:expr) => {
+        $(#[$attr])*
+        #[derive(Clone, Copy, Default)]
+        #[repr(transparent)]
+        pub struct $name<S: ?Sized>(S);
+
+        impl<S: AsRef<str>> $name<S> {
+            /// Wrap the string `s`, which is kept as it is.
+            #[inline]
+            pub fn new(s: S) -> Self {
+                $name(s)
+            }
+
+            /// Unwraps the wrapped string.
+            #[inline]
+            pub fn into_inner(self) -> S {
+                self.0
+            }
+        }
+
+        impl<S: ?Sized + AsRef<str>> $name<S> {
+            /// Wrap the string behind the reference `s`, for example to
+            /// look up a `&str` in a map whose keys own their strings.
+            #[inline]
+            #[allow(unsafe_code)]
+            pub fn from_ref(s: &S) -> &Self {
+                // Safe because the wrapper is `repr(transparent)`.
+                unsafe { &*(s as *const S as *const $name<S>) }
+            }
+
+            /// Gets a reference to the wrapped string.
+            #[inline]
+            pub fn get_ref(&self) -> &S {
+                &self.0
+            }
+        }
+
+        impl<S: AsRef<str>> Borrow<$name<str>> for $name<S> {
+            #[inline]
+            fn borrow(&self) -> &$name<str> {
+                $name::from_ref(self.0.as_ref())
+            }
+        }
+
+        impl<S: AsRef<str>> From<S> for $name<S> {
+            #[inline]
+            fn from(s: S) -> Self {
+                $name(s)
+            }
+        }
+
+        impl<S: ?Sized + AsRef<str>, T: ?Sized + AsRef<str>> PartialEq<$name<T>> for $name<S> {
+            #[inline]
+            fn eq(&self, other: &$name<T>) -> bool {
+                equivalent(self.0.as_ref(), other.0.as_ref(), $form.0, $form.1)
+            }
+        }
+
+        impl<S: ?Sized + AsRef<str>> Eq for $name<S> {}
+
+        impl<S: ?Sized + AsRef<str>, T: ?Sized + AsRef<str>> PartialOrd<$name<T>> for $name<S> {
+            #[inline]
+            fn partial_cmp(&self, other: &$name<T>) -> Option<Ordering> {
+                Some(compare(self.0.as_ref(), other.0.as_ref(), $form.0))
+            }
+        }
+
+        impl<S: ?Sized + AsRef<str>> Ord for $name<S> {
+            #[inline]
+            fn cmp(&self, other: &Self) -> Ordering {
+                compare(self.0.as_ref(), other.0.as_ref(), $form.0)
+            }
+        }
+
+        impl<S: ?Sized + AsRef<str>> Hash for $name<S> {
+            fn hash<H: Hasher>(&self, state: &mut H) {
+                for c in decompose(self.0.as_ref(), $form.0) {
+                    state.write_u32(c as u32);
+                }
+                // Terminate the stream like `str` does, so that keys hashed
+                // one after another in a tuple can't run into each other.
+                state.write_u8(0xff);
+            }
+        }
+
+        impl<S: ?Sized + fmt::Debug> fmt::Debug for $name<S> {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                self.0.fmt(f)
+            }
+        }
+
+        impl<S: ?Sized + fmt::Display> fmt::Display for $name<S> {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                self.0.fmt(f)
+            }
+        }
+    };
+}
+
+equivalence_key! {
+    /// A string wrapper whose `Eq`, `Ord` and `Hash` implementations only
+    /// depend on the string's NFD, so that canonically equivalent strings are
+    /// equal as map keys.
+    ///
+    /// The wrapped string is not modified; comparing and hashing decompose it
+    /// on the fly without allocating. Strings are ordered by the code points of
+    /// their NFD.
+    ///
+    /// Every `CanonicalStr<S>` borrows as a `CanonicalStr<str>`, so a map whose
+    /// keys own their strings can be queried with a borrowed one, wrapped with
+    /// [`CanonicalStr::from_ref`].
+    ///
+    /// ```rust
+    /// use std::collections::HashMap;
+    /// use unicode_normalization::CanonicalStr;
+    ///
+    /// let mut map = HashMap::new();
+    /// map.insert(CanonicalStr::new(String::from("Introducci\u{f3}n")), 1);
+    /// assert_eq!(map.get(CanonicalStr::from_ref("Introduccio\u{301}n")), Some(&1));
+    /// ```
+    CanonicalStr,
+    (Form::Nfd, Form::Nfc)
+}
+
+equivalence_key! {
+    /// A string wrapper whose `Eq`, `Ord` and `Hash` implementations only
+    /// depend on the string's NFKD, so that compatibility equivalent strings
+    /// are equal as map keys.
+    ///
+    /// See [`CanonicalStr`] for details.
+    ///
+    /// ```rust
+    /// use unicode_normalization::CompatibilityStr;
+    ///
+    /// assert_eq!(CompatibilityStr::new("\u{fb01}"), CompatibilityStr::new("fi"));
+    /// ```
+    CompatibilityStr,
+    (Form::Nfkd, Form::Nfkc)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        canonically_equivalent, common_boundary, compatibility_equivalent, CanonicalStr,
+        CompatibilityStr,
+    };
+    use crate::form::Form;
+    use core::borrow::Borrow;
+    use core::cmp::Ordering;
+    use core::hash::{Hash, Hasher};
+
+    #[cfg(not(feature = "std"))]
+    use alloc::{collections::BTreeMap, string::String, vec::Vec};
+    #[cfg(feature = "std")]
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_canonically_equivalent() {
+        assert!(canonically_equivalent("", ""));
+        assert!(canonically_equivalent("abc", "abc"));
+        assert!(canonically_equivalent("\u{c5}", "A\u{30a}"));
+        assert!(canonically_equivalent("\u{212b}", "\u{c5}"));
+        assert!(canonically_equivalent(
+            "a\u{300}\u{305}\u{315}\u{5ae}b",
+            "\u{e0}\u{5ae}\u{305}\u{315}b"
+        ));
+        assert!(canonically_equivalent(
+            "\u{d4db}",
+            "\u{1111}\u{1171}\u{11b6}"
+        ));
+        // The strings only differ in the order of the marks.
+        assert!(canonically_equivalent(
+            "xa\u{323}\u{307}",
+            "xa\u{307}\u{323}"
+        ));
+
+        assert!(!canonically_equivalent("abc", "abd"));
+        assert!(!canonically_equivalent("ab", "abc"));
+        assert!(!canonically_equivalent("\u{e5}", "\u{e1}"));
+        assert!(!canonically_equivalent(
+            "a\u{301}\u{300}",
+            "a\u{300}\u{301}"
+        ));
+        assert!(!canonically_equivalent("\u{2026}", "..."));
+    }
+
+    #[test]
+    fn test_compatibility_equivalent() {
+        assert!(compatibility_equivalent("\u{2026}", "..."));
+        assert!(compatibility_equivalent(
+            "\u{1e0b}\u{1c4}",
+            "d\u{307}DZ\u{30c}"
+        ));
+        assert!(compatibility_equivalent("x\u{2126}", "x\u{3a9}"));
+        assert!(!compatibility_equivalent("\u{2026}", "...."));
+        assert!(!compatibility_equivalent("\u{1c4}", "DZ"));
+    }
+
+    #[test]
+    fn test_common_boundary() {
+        assert_eq!(common_boundary("abc", "abd", Form::Nfd), 2);
+        assert_eq!(common_boundary("abc", "abc", Form::Nfd), 3);
+        // The boundary can't be inside the common prefix of "a\u{301}".
+        assert_eq!(common_boundary("xa\u{301}", "xa\u{300}", Form::Nfd), 1);
+        // The differing characters share their lead byte.
+        assert_eq!(common_boundary("x\u{3b1}", "x\u{3b2}", Form::Nfd), 1);
+        assert_eq!(common_boundary("ab", "a\u{301}", Form::Nfd), 0);
+    }
+
+    /// Records what is written to it, to compare the hashed streams.
+    #[derive(Default)]
+    struct Recorder(Vec<u8>);
+
+    impl Hasher for Recorder {
+        fn finish(&self) -> u64 {
+            0
+        }
+
+        fn write(&mut self, bytes: &[u8]) {
+            self.0.extend_from_slice(bytes);
+        }
+    }
+
+    fn hashed<T: ?Sized + Hash>(t: &T) -> Vec<u8> {
+        let mut recorder = Recorder::default();
+        t.hash(&mut recorder);
+        recorder.0
+    }
+
+    #[test]
+    fn test_canonical_str() {
+        let a = CanonicalStr::new("\u{1e0b}\u{323}");
+        let b = CanonicalStr::new(String::from("\u{1e0d}\u{307}"));
+        let c = CanonicalStr::new("d\u{323}\u{307}");
+        assert!(a == b);
+        assert!(b == c);
+        assert_eq!(hashed(&a), hashed(&b));
+        assert_eq!(hashed(&b), hashed(&c));
+        assert_eq!(a.partial_cmp(&b), Some(Ordering::Equal));
+        assert_eq!(a.get_ref(), &"\u{1e0b}\u{323}");
+
+        // Ordered by the decomposition, so U+00E1 sorts with "a".
+        assert!(CanonicalStr::new("\u{e1}") < CanonicalStr::new("b"));
+        assert!(CanonicalStr::new("a") < CanonicalStr::new("\u{e1}"));
+        assert!(CanonicalStr::new("ab") < CanonicalStr::new("\u{e1}"));
+        assert_eq!(
+            CanonicalStr::new("x\u{e1}").cmp(&CanonicalStr::new("xa\u{301}")),
+            Ordering::Equal
+        );
+
+        assert!(CanonicalStr::new("\u{fb01}") != CanonicalStr::new("fi"));
+        assert_ne!(
+            hashed(&CanonicalStr::new("a")),
+            hashed(&CanonicalStr::new("b"))
+        );
+        // The stream is terminated, so the split between tuple fields counts.
+        assert_ne!(
+            hashed(&(CanonicalStr::new("a"), CanonicalStr::new("b"))),
+            hashed(&(CanonicalStr::new("ab"), CanonicalStr::new("")))
+        );
+    }
+
+    #[test]
+    fn test_borrowed_key() {
+        let owned = CanonicalStr::new(String::from("\u{1e0b}\u{323}"));
+        let borrowed: &CanonicalStr<str> = owned.borrow();
+        assert_eq!(borrowed.get_ref(), "\u{1e0b}\u{323}");
+        // `Borrow` requires the same hash for the key and its borrowed form.
+        assert_eq!(hashed(&owned), hashed(borrowed));
+        assert_eq!(
+            hashed(&owned),
+            hashed(CanonicalStr::from_ref("d\u{323}\u{307}"))
+        );
+
+        let mut map = BTreeMap::new();
+        map.insert(owned, 1);
+        map.insert(CanonicalStr::new(String::from("\u{e1}")), 2);
+        assert_eq!(map.get(CanonicalStr::from_ref("\u{1e0d}\u{307}")), Some(&1));
+        assert_eq!(map.get(CanonicalStr::from_ref("a\u{301}")), Some(&2));
+        assert_eq!(map.get(CanonicalStr::from_ref("a")), None);
+
+        let mut map = BTreeMap::new();
+        map.insert(CompatibilityStr::new(String::from("\u{fb01}")), 3);
+        assert_eq!(map.get(CompatibilityStr::from_ref("fi")), Some(&3));
+    }
+
+    #[test]
+    fn test_compatibility_str() {
+        let a = CompatibilityStr::new("\u{fb01}\u{2026}");
+        let b = CompatibilityStr::new("fi...");
+        assert!(a == b);
+        assert_eq!(hashed(&a), hashed(&b));
+        assert_eq!(a.cmp(&b), Ordering::Equal);
+        assert!(CompatibilityStr::new("\u{2460}") < CompatibilityStr::new("2"));
+    }
+}
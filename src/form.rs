@@ -0,0 +1,169 @@
+// Copyright 2012-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Normalization of UTF-8 text that copies already-normalized spans verbatim
+//! and only runs the `Decompositions`/`Recompositions` iterators over the
+//! segments that need them.
+
+use crate::lookups::canonical_combining_class;
+use crate::quick_check::{quick_check_span, IsNormalized};
+use crate::tables;
+use crate::UnicodeNormalization;
+
+#[cfg(not(feature = "std"))]
+use alloc::{string::String, vec::Vec};
+
+/// One of the four normalization forms.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Form {
+    Nfc,
+    Nfd,
+    Nfkc,
+    Nfkd,
+}
+
+impl Form {
+    #[inline]
+    pub(crate) fn quick_check(self, c: char) -> IsNormalized {
+        match self {
+            Form::Nfc => tables::qc_nfc(c),
+            Form::Nfd => tables::qc_nfd(c),
+            Form::Nfkc => tables::qc_nfkc(c),
+            Form::Nfkd => tables::qc_nfkd(c),
+        }
+    }
+
+    /// Whether there is a normalization boundary before `c`, i.e. whether
+    /// `c` never reorders or composes with any text preceding it.
+    #[inline]
+    pub(crate) fn is_boundary_before(self, c: char) -> bool {
+        c <= '\x7f'
+            || (canonical_combining_class(c) == 0 && self.quick_check(c) == IsNormalized::Yes)
+    }
+
+    /// The length of the segment at the start of `s`, which extends to the
+    /// next normalization boundary after its first character.
+    #[inline]
+    pub(crate) fn segment_len(self, s: &str) -> usize {
+        s.char_indices()
+            .skip(1)
+            .find(|&(_, c)| self.is_boundary_before(c))
+            .map_or(s.len(), |(i, _)| i)
+    }
+
+    /// Normalize `iter` through the character iterators, passing each
+    /// resulting character to `emit`.
+    pub(crate) fn normalize_chars<I, F>(self, iter: I, emit: F)
+    where
+        I: Iterator<Item = char>,
+        F: FnMut(char),
+    {
+        match self {
+            Form::Nfc => iter.nfc().for_each(emit),
+            Form::Nfd => iter.nfd().for_each(emit),
+            Form::Nfkc => iter.nfkc().for_each(emit),
+            Form::Nfkd => iter.nfkd().for_each(emit),
+        }
+    }
+}
+
+/// A growable UTF-8 buffer that normalized text can be appended to.
+pub(crate) trait Sink {
+    fn push_str(&mut self, s: &str);
+    fn push_char(&mut self, c: char);
+}
+
+impl Sink for String {
+    #[inline]
+    fn push_str(&mut self, s: &str) {
+        String::push_str(self, s)
+    }
+
+    #[inline]
+    fn push_char(&mut self, c: char) {
+        self.push(c)
+    }
+}
+
+impl Sink for Vec<u8> {
+    #[inline]
+    fn push_str(&mut self, s: &str) {
+        self.extend_from_slice(s.as_bytes())
+    }
+
+    #[inline]
+    fn push_char(&mut self, c: char) {
+        let mut buf = [0; 4];
+        self.extend_from_slice(c.encode_utf8(&mut buf).as_bytes())
+    }
+}
+
+/// Append the normalization of `s` to `out`.
+///
+/// Runs of text that the quick check proves normalized are copied as byte
+/// spans; only the segments between the surrounding normalization boundaries
+/// of a `No` or `Maybe` character are decoded and normalized.
+pub(crate) fn normalize_to<S: Sink + ?Sized>(form: Form, mut s: &str, out: &mut S) {
+    loop {
+        let boundary = quick_check_span(s, form);
+        out.push_str(&s[..boundary]);
+        if boundary == s.len() {
+            return;
+        }
+
+        // `quick_check_span` stops on the first character that is not known
+        // to be normalized and there are no boundaries between it and
+        // `boundary`, so the segment extends to the next boundary after the
+        // segment's first character.
+        let s_tail = &s[boundary..];
+        let end = form.segment_len(s_tail);
+        form.normalize_chars(s_tail[..end].chars(), |c| out.push_char(c));
+        s = &s_tail[end..];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{normalize_to, Form};
+    use crate::UnicodeNormalization;
+
+    #[cfg(not(feature = "std"))]
+    use alloc::string::String;
+
+    #[test]
+    fn test_normalize_to() {
+        let inputs = [
+            "",
+            "abc",
+            "Introduccio\u{0301}n a Unicode.pdf",
+            "a\u{300}\u{305}\u{315}\u{5ae}b x\u{1e0b}\u{323}",
+            "\u{1e0b}\u{1c4}\u{2026}\u{2126}",
+            "\u{1100}\u{1161}\u{11a8} \u{1161}\u{1100}",
+            "\u{301}a\u{301}\u{301}",
+        ];
+        for s in &inputs {
+            let mut out = String::new();
+            normalize_to(Form::Nfc, s, &mut out);
+            assert_eq!(out, s.nfc().collect::<String>());
+
+            let mut out = String::new();
+            normalize_to(Form::Nfd, s, &mut out);
+            assert_eq!(out, s.nfd().collect::<String>());
+
+            let mut out = String::new();
+            normalize_to(Form::Nfkc, s, &mut out);
+            assert_eq!(out, s.nfkc().collect::<String>());
+
+            let mut out = String::new();
+            normalize_to(Form::Nfkd, s, &mut out);
+            assert_eq!(out, s.nfkd().collect::<String>());
+        }
+    }
+}
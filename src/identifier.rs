@@ -0,0 +1,183 @@
+// Copyright 2012-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Identifiers as described in
+//! [Unicode Standard Annex #31](https://www.unicode.org/reports/tr31/).
+//!
+//! An identifier is a character with the `XID_Start` property followed by any
+//! number of characters with the `XID_Continue` property (the default
+//! identifier syntax, UAX31-D1). Identifiers are compared after normalizing
+//! them, to NFC like Rust and JavaScript do or to NFKC like Python does
+//! (UAX31-R4).
+//!
+//! `XID_Start` and `XID_Continue` differ from `ID_Start` and `ID_Continue` by
+//! the modifications of
+//! [UAX #31 section 5.1.3](https://www.unicode.org/reports/tr31/#NFKC_Modifications),
+//! which remove the few characters whose NFKC wouldn't be an identifier, such
+//! as U+037A GREEK YPOGEGRAMMENI (NFKC: a space and an iota). That makes the
+//! identifiers closed under NFC and NFKC: normalizing a valid identifier always
+//! results in a valid identifier.
+//!
+//! Languages which allow additional characters, such as `_` at the start of
+//! Rust and Python identifiers or `$` in JavaScript, can build their own check
+//! on [`is_xid_start`] and [`is_xid_continue`].
+//!
+//! ```rust
+//! use unicode_normalization::identifier;
+//!
+//! assert!(identifier::is_identifier("r\u{e9}sum\u{e9}"));
+//! assert!(!identifier::is_identifier("9lives"));
+//!
+//! // The "fi" ligature is normalized away in NFKC, but not in NFC.
+//! let python = identifier::nfkc_identifier("\u{fb01}le").unwrap();
+//! assert_eq!(python, "file");
+//! let rust = identifier::nfc_identifier("\u{fb01}le").unwrap();
+//! assert_eq!(rust, "\u{fb01}le");
+//! ```
+
+use crate::quick_check::{is_nfc, is_nfkc};
+use crate::string::{NfcString, NfkcString};
+
+pub use crate::lookups::{is_xid_continue, is_xid_start};
+
+/// Return whether the string is an identifier in the default identifier
+/// syntax (UAX31-D1): an `XID_Start` character followed by `XID_Continue`
+/// characters.
+///
+/// The string isn't required to be normalized.
+pub fn is_identifier(s: &str) -> bool {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(c) if is_start(c) => chars.all(is_continue),
+        _ => false,
+    }
+}
+
+/// Return whether the string is an identifier and in NFC, the form in which
+/// identifiers are compared by [`nfc_identifier`].
+#[inline]
+pub fn is_nfc_identifier(s: &str) -> bool {
+    is_identifier(s) && is_nfc(s)
+}
+
+/// Return whether the string is an identifier and in NFKC, the form in which
+/// identifiers are compared by [`nfkc_identifier`].
+#[inline]
+pub fn is_nfkc_identifier(s: &str) -> bool {
+    is_identifier(s) && is_nfkc(s)
+}
+
+/// Convert an identifier to NFC, returning `None` if `s` is not an
+/// identifier.
+///
+/// Two identifiers are equivalent under the NFC profile of UAX31-R4 if they
+/// result in the same string.
+#[inline]
+pub fn nfc_identifier(s: &str) -> Option<NfcString> {
+    if is_identifier(s) {
+        Some(NfcString::normalize(s))
+    } else {
+        None
+    }
+}
+
+/// Convert an identifier to NFKC, returning `None` if `s` is not an
+/// identifier.
+///
+/// Two identifiers are equivalent under the NFKC profile of UAX31-R4 if they
+/// result in the same string.
+#[inline]
+pub fn nfkc_identifier(s: &str) -> Option<NfkcString> {
+    if is_identifier(s) {
+        Some(NfkcString::normalize(s))
+    } else {
+        None
+    }
+}
+
+#[inline]
+fn is_start(c: char) -> bool {
+    if c.is_ascii() {
+        c.is_ascii_alphabetic()
+    } else {
+        is_xid_start(c)
+    }
+}
+
+#[inline]
+fn is_continue(c: char) -> bool {
+    if c.is_ascii() {
+        c.is_ascii_alphanumeric() || c == '_'
+    } else {
+        is_xid_continue(c)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        is_identifier, is_nfc_identifier, is_nfkc_identifier, is_xid_continue, is_xid_start,
+        nfc_identifier, nfkc_identifier,
+    };
+
+    #[test]
+    fn test_xid() {
+        assert!(is_xid_start('a'));
+        assert!(!is_xid_start('_'));
+        assert!(is_xid_continue('_'));
+        assert!(!is_xid_start('1'));
+        assert!(is_xid_continue('1'));
+        assert!(is_xid_start('\u{3b1}'));
+        assert!(is_xid_start('\u{4e00}'));
+        assert!(is_xid_start('\u{2f800}'));
+        assert!(!is_xid_start('\u{301}'));
+        assert!(is_xid_continue('\u{301}'));
+        assert!(!is_xid_continue('\u{2026}'));
+        assert!(!is_xid_continue('\u{10ffff}'));
+
+        // The NFKC modifications of UAX #31: U+037A is `ID_Start` but its NFKC
+        // starts with a space, and U+0E33 is `ID_Start` but its NFKC ends in a
+        // vowel which is only `XID_Continue`.
+        assert!(!is_xid_start('\u{37a}'));
+        assert!(!is_xid_continue('\u{37a}'));
+        assert!(!is_xid_start('\u{e33}'));
+        assert!(is_xid_continue('\u{e33}'));
+    }
+
+    #[test]
+    fn test_is_identifier() {
+        assert!(is_identifier("x"));
+        assert!(is_identifier("snake_case_2"));
+        assert!(is_identifier("\u{3b1}\u{3b2}\u{3b3}"));
+        assert!(is_identifier("e\u{301}"));
+        assert!(!is_identifier(""));
+        assert!(!is_identifier("_x"));
+        assert!(!is_identifier("2x"));
+        assert!(!is_identifier("kebab-case"));
+        assert!(!is_identifier("\u{301}e"));
+
+        assert!(is_nfc_identifier("\u{e9}"));
+        assert!(!is_nfc_identifier("e\u{301}"));
+        assert!(is_nfc_identifier("\u{fb01}"));
+        assert!(!is_nfkc_identifier("\u{fb01}"));
+    }
+
+    #[test]
+    fn test_normalized_identifier() {
+        assert_eq!(nfc_identifier("e\u{301}").unwrap(), "\u{e9}");
+        assert_eq!(nfkc_identifier("e\u{301}").unwrap(), "\u{e9}");
+        assert_eq!(nfkc_identifier("\u{210c}").unwrap(), "H");
+        assert_eq!(nfkc_identifier("\u{2160}x").unwrap(), "Ix");
+        assert_eq!(nfkc_identifier("x\u{b2}"), None);
+        assert_eq!(nfkc_identifier("abc").unwrap(), "abc");
+        assert_eq!(nfc_identifier("a b"), None);
+        assert_eq!(nfkc_identifier("\u{b2}"), None);
+    }
+}
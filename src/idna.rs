@@ -0,0 +1,887 @@
+// Copyright 2012-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Internationalized domain names as processed by
+//! [Unicode Technical Standard #46](https://www.unicode.org/reports/tr46/).
+//!
+//! UTS #46 maps a domain name with the IDNA Mapping Table, which lowercases
+//! it, applies compatibility mappings and removes ignored characters, then
+//! normalizes it to NFC and splits it into labels at U+002E FULL STOP. Labels
+//! starting with `xn--` are decoded from Punycode, and every label is checked
+//! against the validity criteria of section 4.1. [`to_ascii`] finally encodes
+//! the non-ASCII labels back to Punycode, while [`to_unicode`] returns the
+//! processed labels as they are.
+//!
+//! The mapping table is generated for the same version of Unicode as the
+//! normalization tables, [`UNICODE_VERSION`](crate::UNICODE_VERSION), so the
+//! mapped domain name is always normalized consistently with the rest of
+//! this crate.
+//!
+//! ```rust
+//! use unicode_normalization::idna::{self, Config, ErrorKind, Processing};
+//!
+//! assert_eq!(idna::to_ascii("B\u{fc}cher.example").unwrap(), "xn--bcher-kva.example");
+//! assert_eq!(idna::to_unicode("xn--bcher-kva.example").0, "b\u{fc}cher.example");
+//!
+//! // The deviation characters are kept by Nontransitional Processing, the
+//! // default, and mapped like IDNA2003 did by Transitional Processing.
+//! assert_eq!(idna::to_ascii("fa\u{df}.de").unwrap(), "xn--fa-hia.de");
+//! let transitional = Config::new().processing(Processing::Transitional);
+//! assert_eq!(transitional.to_ascii("fa\u{df}.de").unwrap(), "fass.de");
+//!
+//! let error = idna::to_ascii("-example.com").unwrap_err();
+//! assert_eq!(error.kind(), ErrorKind::Hyphens);
+//! assert_eq!(error.label(), 0);
+//! ```
+
+use crate::in_place::normalize_nfc_in_place;
+use crate::lookups::{
+    bidi_class, canonical_combining_class, idna_mapped, is_combining_mark, is_idna_valid,
+    joining_type,
+};
+pub(crate) use crate::properties::{BidiClass, JoiningType};
+use crate::quick_check::is_nfc;
+use crate::tables::{idna_deviation, is_idna_ignored};
+use core::char;
+use core::fmt;
+
+#[cfg(not(feature = "std"))]
+use alloc::{string::String, vec::Vec};
+
+/// The processing mode of UTS #46, which only affects the deviation
+/// characters: U+00DF ß, U+03C2 ς, U+200C ZERO WIDTH NON-JOINER and U+200D
+/// ZERO WIDTH JOINER.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Processing {
+    /// Map the deviation characters the way IDNA2003 did: ß to "ss", ς to σ,
+    /// and remove the joiners. UTS #46 deprecates this mode, which only
+    /// remains for compatibility with older registrations.
+    Transitional,
+    /// Keep the deviation characters as IDNA2008 does.
+    Nontransitional,
+}
+
+/// The options of UTS #46 processing.
+///
+/// [`Config::new`] returns the strictest configuration: Nontransitional
+/// Processing with `CheckHyphens`, `CheckBidi`, `CheckJoiners`,
+/// `UseSTD3ASCIIRules` and `VerifyDnsLength` all set, which is appropriate
+/// for validating host names. The WHATWG URL Standard, for example, clears
+/// `CheckHyphens`, and unless it is strict also `UseSTD3ASCIIRules` and
+/// `VerifyDnsLength`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Config {
+    processing: Processing,
+    check_hyphens: bool,
+    check_bidi: bool,
+    check_joiners: bool,
+    use_std3_ascii_rules: bool,
+    verify_dns_length: bool,
+}
+
+impl Default for Config {
+    #[inline]
+    fn default() -> Self {
+        Config::new()
+    }
+}
+
+impl Config {
+    /// Create the default configuration, with every check enabled.
+    #[inline]
+    pub fn new() -> Self {
+        Config {
+            processing: Processing::Nontransitional,
+            check_hyphens: true,
+            check_bidi: true,
+            check_joiners: true,
+            use_std3_ascii_rules: true,
+            verify_dns_length: true,
+        }
+    }
+
+    /// Set the processing mode.
+    #[inline]
+    pub fn processing(mut self, processing: Processing) -> Self {
+        self.processing = processing;
+        self
+    }
+
+    /// Set `CheckHyphens`: reject labels starting or ending with a hyphen,
+    /// or with hyphens in both their third and fourth positions. When it is
+    /// cleared, only labels starting with `xn--` are rejected.
+    #[inline]
+    pub fn check_hyphens(mut self, check_hyphens: bool) -> Self {
+        self.check_hyphens = check_hyphens;
+        self
+    }
+
+    /// Set `CheckBidi`: reject labels breaking the Bidi Rule of
+    /// [RFC 5893](https://www.rfc-editor.org/rfc/rfc5893#section-2) in domain
+    /// names containing right-to-left characters.
+    #[inline]
+    pub fn check_bidi(mut self, check_bidi: bool) -> Self {
+        self.check_bidi = check_bidi;
+        self
+    }
+
+    /// Set `CheckJoiners`: reject the joiners U+200C and U+200D outside of the
+    /// contexts allowed by the CONTEXTJ rules of
+    /// [RFC 5892](https://www.rfc-editor.org/rfc/rfc5892#appendix-A).
+    #[inline]
+    pub fn check_joiners(mut self, check_joiners: bool) -> Self {
+        self.check_joiners = check_joiners;
+        self
+    }
+
+    /// Set `UseSTD3ASCIIRules`: only allow the ASCII letters, digits and
+    /// hyphen in labels, as in host names.
+    #[inline]
+    pub fn use_std3_ascii_rules(mut self, use_std3_ascii_rules: bool) -> Self {
+        self.use_std3_ascii_rules = use_std3_ascii_rules;
+        self
+    }
+
+    /// Set `VerifyDnsLength`: make [`Config::to_ascii`] reject empty labels,
+    /// labels longer than 63 bytes and domain names longer than 253 bytes.
+    /// Like the conformance tests of UTS #46, this also rejects the empty
+    /// root label of a domain name ending with a full stop.
+    #[inline]
+    pub fn verify_dns_length(mut self, verify_dns_length: bool) -> Self {
+        self.verify_dns_length = verify_dns_length;
+        self
+    }
+
+    /// Convert a domain name to its ASCII form, with the non-ASCII labels
+    /// encoded to Punycode (UTS #46 ToASCII).
+    ///
+    /// Returns the error in the first invalid label if the domain name is not
+    /// valid.
+    pub fn to_ascii(&self, domain: &str) -> Result<String, Error> {
+        let (unicode, result) = self.to_unicode(domain);
+        result?;
+
+        let mut out = String::with_capacity(unicode.len());
+        for (i, label) in unicode.split('.').enumerate() {
+            if i > 0 {
+                out.push('.');
+            }
+            if label.is_ascii() {
+                out.push_str(label);
+            } else {
+                out.push_str("xn--");
+                if encode_punycode(label, &mut out).is_none() {
+                    return Err(Error::new(ErrorKind::Punycode, i));
+                }
+            }
+        }
+
+        if self.verify_dns_length {
+            verify_dns_length(&out)?;
+        }
+        Ok(out)
+    }
+
+    /// Convert a domain name to its Unicode form, with the labels starting
+    /// with `xn--` decoded from Punycode (UTS #46 ToUnicode).
+    ///
+    /// The domain name is always converted, and returned along with the
+    /// error in the first invalid label, if any.
+    pub fn to_unicode(&self, domain: &str) -> (String, Result<(), Error>) {
+        let mapped = map(domain, self.processing);
+        let mut out = String::with_capacity(mapped.len());
+        let mut result = Ok(());
+
+        // Decode all the labels before checking them, since whether the Bidi
+        // Rule applies depends on the whole domain name.
+        let mut labels = Vec::new();
+        for (i, label) in mapped.split('.').enumerate() {
+            if i > 0 {
+                out.push('.');
+            }
+            let start = out.len();
+            if !label.starts_with("xn--") {
+                out.push_str(label);
+                labels.push((i, start, out.len(), self.processing, false));
+                continue;
+            }
+
+            if label.is_ascii() && decode_punycode(&label[4..], &mut out).is_some() {
+                if out[start..].is_ascii() {
+                    record(&mut result, ErrorKind::Punycode, i);
+                }
+                // Decoded labels are always checked for Nontransitional
+                // Processing, so that they round trip.
+                labels.push((i, start, out.len(), Processing::Nontransitional, true));
+            } else {
+                record(&mut result, ErrorKind::Punycode, i);
+                out.truncate(start);
+                out.push_str(label);
+            }
+        }
+
+        let bidi = self.check_bidi && out.chars().any(is_rtl);
+        for (i, start, end, processing, punycode) in labels {
+            if let Err(kind) = self.check_label(&out[start..end], processing, punycode, bidi) {
+                record(&mut result, kind, i);
+            }
+        }
+        (out, result)
+    }
+
+    /// Check a label against the validity criteria of UTS #46 section 4.1.
+    fn check_label(
+        &self,
+        label: &str,
+        processing: Processing,
+        punycode: bool,
+        bidi: bool,
+    ) -> Result<(), ErrorKind> {
+        // Labels which weren't decoded were normalized along with the domain
+        // name, and can't be split in the middle of a composition since the
+        // full stop is a starter which never composes.
+        if punycode && !is_nfc(label) {
+            return Err(ErrorKind::NotNormalized);
+        }
+
+        if self.check_hyphens {
+            let mut third_and_fourth = label.chars().skip(2);
+            if label.starts_with('-')
+                || label.ends_with('-')
+                || (third_and_fourth.next() == Some('-') && third_and_fourth.next() == Some('-'))
+            {
+                return Err(ErrorKind::Hyphens);
+            }
+        } else if label.starts_with("xn--") {
+            return Err(ErrorKind::Hyphens);
+        }
+
+        if label.chars().next().map_or(false, is_combining_mark) {
+            return Err(ErrorKind::LeadingCombiningMark);
+        }
+
+        for c in label.chars() {
+            let valid = if c.is_ascii() {
+                // The full stop can only be found in a decoded label.
+                c != '.'
+                    && !c.is_ascii_uppercase()
+                    && (!self.use_std3_ascii_rules || c.is_ascii_alphanumeric() || c == '-')
+            } else {
+                is_idna_valid(c)
+                    || (processing == Processing::Nontransitional && idna_deviation(c).is_some())
+            };
+            if !valid {
+                return Err(ErrorKind::DisallowedCharacter(c));
+            }
+        }
+
+        if self.check_joiners && !check_joiners(label) {
+            return Err(ErrorKind::Joiners);
+        }
+        if bidi && !check_bidi(label) {
+            return Err(ErrorKind::Bidi);
+        }
+        Ok(())
+    }
+}
+
+/// Convert a domain name to its ASCII form with the default [`Config`].
+#[inline]
+pub fn to_ascii(domain: &str) -> Result<String, Error> {
+    Config::new().to_ascii(domain)
+}
+
+/// Convert a domain name to its Unicode form with the default [`Config`].
+#[inline]
+pub fn to_unicode(domain: &str) -> (String, Result<(), Error>) {
+    Config::new().to_unicode(domain)
+}
+
+/// The kind of an [`Error`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ErrorKind {
+    /// A character which isn't allowed in a label, either because of its
+    /// status in the IDNA Mapping Table or because of `UseSTD3ASCIIRules`.
+    DisallowedCharacter(char),
+    /// A label starting with `xn--` which isn't valid Punycode, is too long
+    /// to be decoded or decodes to an ASCII label, or a label which is too
+    /// long to be encoded.
+    Punycode,
+    /// A label decoded from Punycode which isn't in NFC.
+    NotNormalized,
+    /// A label breaking `CheckHyphens`.
+    Hyphens,
+    /// A label starting with a combining mark.
+    LeadingCombiningMark,
+    /// A label breaking `CheckJoiners`.
+    Joiners,
+    /// A label breaking `CheckBidi`.
+    Bidi,
+    /// An empty label, with `VerifyDnsLength`.
+    EmptyLabel,
+    /// A label longer than 63 bytes, with `VerifyDnsLength`.
+    LabelTooLong,
+    /// A domain name longer than 253 bytes, with `VerifyDnsLength`.
+    DomainTooLong,
+}
+
+/// An error returned when a domain name is not valid.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Error {
+    kind: ErrorKind,
+    label: usize,
+}
+
+impl Error {
+    #[inline]
+    fn new(kind: ErrorKind, label: usize) -> Self {
+        Error { kind, label }
+    }
+
+    /// Returns the kind of error.
+    #[inline]
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+
+    /// Returns the index of the label which caused this error. For
+    /// [`ErrorKind::DomainTooLong`], this is the label which goes past the
+    /// length limit.
+    #[inline]
+    pub fn label(&self) -> usize {
+        self.label
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.kind {
+            ErrorKind::DisallowedCharacter(c) => {
+                write!(f, "disallowed character {:?} in label {}", c, self.label)
+            }
+            ErrorKind::Punycode => write!(f, "invalid Punycode in label {}", self.label),
+            ErrorKind::NotNormalized => write!(f, "label {} is not in NFC", self.label),
+            ErrorKind::Hyphens => write!(f, "misplaced hyphens in label {}", self.label),
+            ErrorKind::LeadingCombiningMark => {
+                write!(f, "label {} starts with a combining mark", self.label)
+            }
+            ErrorKind::Joiners => write!(f, "misplaced joiners in label {}", self.label),
+            ErrorKind::Bidi => write!(f, "label {} breaks the Bidi Rule", self.label),
+            ErrorKind::EmptyLabel => write!(f, "label {} is empty", self.label),
+            ErrorKind::LabelTooLong => write!(f, "label {} is too long", self.label),
+            ErrorKind::DomainTooLong => {
+                write!(f, "domain name is too long at label {}", self.label)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+/// Record an error, keeping the one in the first label.
+#[inline]
+fn record(result: &mut Result<(), Error>, kind: ErrorKind, label: usize) {
+    match *result {
+        Err(ref error) if error.label <= label => {}
+        _ => *result = Err(Error::new(kind, label)),
+    }
+}
+
+/// Apply the IDNA Mapping Table to a domain name, then normalize it to NFC.
+fn map(domain: &str, processing: Processing) -> String {
+    let mut out = String::with_capacity(domain.len());
+    for c in domain.chars() {
+        if c.is_ascii() {
+            out.push(c.to_ascii_lowercase());
+        } else if let Some(mapped) = idna_mapped(c) {
+            // U+1E9E LATIN CAPITAL LETTER SHARP S maps to ß, which
+            // Transitional Processing maps further to "ss".
+            for &c in mapped {
+                push_deviation(c, processing, &mut out);
+            }
+        } else if !is_idna_ignored(c) {
+            // Valid and disallowed characters are kept, the latter to be
+            // reported by the validity checks.
+            push_deviation(c, processing, &mut out);
+        }
+    }
+    normalize_nfc_in_place(&mut out);
+    out
+}
+
+#[inline]
+fn push_deviation(c: char, processing: Processing, out: &mut String) {
+    match idna_deviation(c) {
+        Some(mapped) if processing == Processing::Transitional => out.extend(mapped),
+        _ => out.push(c),
+    }
+}
+
+fn verify_dns_length(domain: &str) -> Result<(), Error> {
+    let mut end = 0;
+    for (i, label) in domain.split('.').enumerate() {
+        end += label.len();
+        if label.is_empty() {
+            return Err(Error::new(ErrorKind::EmptyLabel, i));
+        }
+        if label.len() > 63 {
+            return Err(Error::new(ErrorKind::LabelTooLong, i));
+        }
+        if end > 253 {
+            return Err(Error::new(ErrorKind::DomainTooLong, i));
+        }
+        end += 1;
+    }
+    Ok(())
+}
+
+#[inline]
+pub(crate) fn is_rtl(c: char) -> bool {
+    match bidi_class(c) {
+        BidiClass::R | BidiClass::AL | BidiClass::AN => true,
+        _ => false,
+    }
+}
+
+/// Check the Bidi Rule of RFC 5893 section 2.
+pub(crate) fn check_bidi(label: &str) -> bool {
+    use self::BidiClass::*;
+
+    let rtl = match label.chars().next().map(bidi_class) {
+        None => return true,
+        Some(L) => false,
+        Some(R) | Some(AL) => true,
+        Some(_) => return false,
+    };
+    let last = label
+        .chars()
+        .rev()
+        .map(bidi_class)
+        .find(|&class| class != NSM);
+
+    if rtl {
+        let (mut en, mut an) = (false, false);
+        for class in label.chars().map(bidi_class) {
+            match class {
+                R | AL | ES | CS | ET | ON | BN | NSM => {}
+                EN => en = true,
+                AN => an = true,
+                _ => return false,
+            }
+        }
+        match last {
+            Some(R) | Some(AL) | Some(EN) | Some(AN) => !(en && an),
+            _ => false,
+        }
+    } else {
+        let valid = label.chars().map(bidi_class).all(|class| match class {
+            L | EN | ES | CS | ET | ON | BN | NSM => true,
+            _ => false,
+        });
+        match last {
+            Some(L) | Some(EN) => valid,
+            _ => false,
+        }
+    }
+}
+
+const ZWNJ: char = '\u{200c}';
+const ZWJ: char = '\u{200d}';
+
+/// Check the CONTEXTJ rules of RFC 5892 appendix A.
+fn check_joiners(label: &str) -> bool {
+    label
+        .char_indices()
+        .all(|(i, c)| (c != ZWNJ && c != ZWJ) || joiner_allowed(label, i, c))
+}
+
+/// Return whether the joiner `c` at byte offset `i` in `s` satisfies its
+/// CONTEXTJ rule.
+pub(crate) fn joiner_allowed(s: &str, i: usize, c: char) -> bool {
+    const VIRAMA: u8 = 9;
+
+    let (before, after) = (&s[..i], &s[i + c.len_utf8()..]);
+    if before.chars().next_back().map(canonical_combining_class) == Some(VIRAMA) {
+        return true;
+    }
+    if c == ZWJ {
+        return false;
+    }
+
+    // (Joining_Type:{L,D})(Joining_Type:T)*\u200C(Joining_Type:T)*(Joining_Type:{R,D})
+    let not_transparent = |t: &Option<JoiningType>| *t != Some(JoiningType::Transparent);
+    let left = before.chars().rev().map(joining_type).find(not_transparent);
+    let right = after.chars().map(joining_type).find(not_transparent);
+    match (left, right) {
+        (Some(Some(JoiningType::Left)), Some(Some(JoiningType::Right)))
+        | (Some(Some(JoiningType::Left)), Some(Some(JoiningType::Dual)))
+        | (Some(Some(JoiningType::Dual)), Some(Some(JoiningType::Right)))
+        | (Some(Some(JoiningType::Dual)), Some(Some(JoiningType::Dual))) => true,
+        _ => false,
+    }
+}
+
+// Punycode parameters from RFC 3492 section 5.
+const BASE: u32 = 36;
+const T_MIN: u32 = 1;
+const T_MAX: u32 = 26;
+const SKEW: u32 = 38;
+const DAMP: u32 = 700;
+const INITIAL_BIAS: u32 = 72;
+const INITIAL_N: u32 = 0x80;
+
+// Longer labels are rejected before decoding or encoding them, since both
+// take time quadratic in the length of the label. These are the limits of
+// the `idna` crate, far above the 63 bytes that DNS allows.
+const DECODE_MAX_LEN: usize = 2000;
+const ENCODE_MAX_LEN: usize = 1000;
+
+#[inline]
+fn adapt(mut delta: u32, num_points: u32, first_time: bool) -> u32 {
+    delta /= if first_time { DAMP } else { 2 };
+    delta += delta / num_points;
+    let mut k = 0;
+    while delta > ((BASE - T_MIN) * T_MAX) / 2 {
+        delta /= BASE - T_MIN;
+        k += BASE;
+    }
+    k + (BASE - T_MIN + 1) * delta / (delta + SKEW)
+}
+
+#[inline]
+fn threshold(k: u32, bias: u32) -> u32 {
+    if k <= bias {
+        T_MIN
+    } else if k >= bias + T_MAX {
+        T_MAX
+    } else {
+        k - bias
+    }
+}
+
+/// Decode a Punycode label without its `xn--` prefix, returning `None` if it
+/// isn't valid or is longer than `DECODE_MAX_LEN` bytes.
+fn decode_punycode(input: &str, out: &mut String) -> Option<()> {
+    if input.len() > DECODE_MAX_LEN {
+        return None;
+    }
+    // As in the sample implementation of RFC 3492, a delimiter at the start
+    // is taken as a digit, which is invalid.
+    let (basic, extended) = match input.rfind('-') {
+        Some(i) if i > 0 => (&input[..i], &input[i + 1..]),
+        _ => ("", input),
+    };
+    let mut output: Vec<char> = basic.chars().collect();
+
+    let mut n = INITIAL_N;
+    let mut i: u32 = 0;
+    let mut bias = INITIAL_BIAS;
+    let mut digits = extended.bytes().peekable();
+    while digits.peek().is_some() {
+        let old_i = i;
+        let mut w: u32 = 1;
+        let mut k = BASE;
+        loop {
+            let digit = match digits.next()? {
+                b @ b'a'..=b'z' => b - b'a',
+                b @ b'A'..=b'Z' => b - b'A',
+                b @ b'0'..=b'9' => b - b'0' + 26,
+                _ => return None,
+            } as u32;
+            i = i.checked_add(digit.checked_mul(w)?)?;
+            let t = threshold(k, bias);
+            if digit < t {
+                break;
+            }
+            w = w.checked_mul(BASE - t)?;
+            k += BASE;
+        }
+
+        let len = output.len() as u32 + 1;
+        bias = adapt(i - old_i, len, old_i == 0);
+        n = n.checked_add(i / len)?;
+        i %= len;
+        output.insert(i as usize, char::from_u32(n)?);
+        i += 1;
+    }
+
+    out.extend(output);
+    Some(())
+}
+
+/// Encode a label to Punycode, returning `None` if it is longer than
+/// `ENCODE_MAX_LEN` characters. Below that, the deltas can't overflow a `u32`.
+fn encode_punycode(input: &str, out: &mut String) -> Option<()> {
+    let len = input.chars().count();
+    if len > ENCODE_MAX_LEN {
+        return None;
+    }
+    let mut basic = 0;
+    for c in input.chars().filter(|c| c.is_ascii()) {
+        out.push(c);
+        basic += 1;
+    }
+    if basic > 0 {
+        out.push('-');
+    }
+
+    let len = len as u32;
+    let mut n = INITIAL_N;
+    let mut delta: u32 = 0;
+    let mut bias = INITIAL_BIAS;
+    let mut h = basic;
+    while h < len {
+        let m = input.chars().map(u32::from).filter(|&c| c >= n).min()?;
+        delta = delta.checked_add((m - n).checked_mul(h + 1)?)?;
+        n = m;
+        for c in input.chars().map(u32::from) {
+            if c < n {
+                delta = delta.checked_add(1)?;
+            } else if c == n {
+                let mut q = delta;
+                let mut k = BASE;
+                loop {
+                    let t = threshold(k, bias);
+                    if q < t {
+                        break;
+                    }
+                    out.push(punycode_digit(t + (q - t) % (BASE - t)));
+                    q = (q - t) / (BASE - t);
+                    k += BASE;
+                }
+                out.push(punycode_digit(q));
+                bias = adapt(delta, h + 1, h == basic);
+                delta = 0;
+                h += 1;
+            }
+        }
+        delta = delta.checked_add(1)?;
+        n += 1;
+    }
+    Some(())
+}
+
+#[inline]
+fn punycode_digit(d: u32) -> char {
+    if d < 26 {
+        (b'a' + d as u8) as char
+    } else {
+        (b'0' + (d - 26) as u8) as char
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        decode_punycode, encode_punycode, to_ascii, to_unicode, Config, ErrorKind, Processing,
+    };
+
+    #[cfg(not(feature = "std"))]
+    use alloc::string::String;
+
+    #[test]
+    fn test_punycode() {
+        // Samples from RFC 3492 section 7.1.
+        let samples = [
+            (
+                "\u{644}\u{64a}\u{647}\u{645}\u{627}\u{628}\u{62a}\u{643}\u{644}\u{645}\u{648}\
+                 \u{634}\u{639}\u{631}\u{628}\u{64a}\u{61f}",
+                "egbpdaj6bu4bxfgehfvwxn",
+            ),
+            (
+                "\u{4ed6}\u{4eec}\u{4e3a}\u{4ec0}\u{4e48}\u{4e0d}\u{8bf4}\u{4e2d}\u{6587}",
+                "ihqwcrb4cv8a8dqg056pqjye",
+            ),
+            (
+                "3\u{5e74}b\u{7d44}\u{91d1}\u{516b}\u{5148}\u{751f}",
+                "3b-ww4c5e180e575a65lsy2b",
+            ),
+            ("b\u{fc}cher", "bcher-kva"),
+            ("-> $1.00 <-", "-> $1.00 <--"),
+        ];
+        for &(decoded, encoded) in samples.iter() {
+            let mut out = String::new();
+            encode_punycode(decoded, &mut out).unwrap();
+            assert_eq!(out, encoded);
+            let mut out = String::new();
+            decode_punycode(encoded, &mut out).unwrap();
+            assert_eq!(out, decoded);
+        }
+
+        let mut out = String::new();
+        assert!(decode_punycode("-abc", &mut out).is_none());
+        assert!(decode_punycode("a\u{e9}", &mut out).is_none());
+        assert!(decode_punycode("99999999999", &mut out).is_none());
+    }
+
+    #[test]
+    fn test_long_labels() {
+        // Rejected up front rather than taking quadratic time.
+        let long = "\u{4e00}".repeat(1_000_000);
+        let lenient = Config::new().verify_dns_length(false);
+        assert_eq!(
+            lenient.to_ascii(&long).unwrap_err().kind(),
+            ErrorKind::Punycode
+        );
+        let encoded = String::from("xn--") + &"a".repeat(4_000_000);
+        assert_eq!(
+            lenient.to_unicode(&encoded).1.unwrap_err().kind(),
+            ErrorKind::Punycode
+        );
+
+        // The longest labels are still converted.
+        let label = "\u{4e00}".repeat(1000);
+        let ascii = lenient.to_ascii(&label).unwrap();
+        assert_eq!(lenient.to_unicode(&ascii), (label, Ok(())));
+    }
+
+    #[test]
+    fn test_mapping() {
+        assert_eq!(to_ascii("EXAMPLE.com").unwrap(), "example.com");
+        assert_eq!(
+            to_ascii("\u{ff25}\u{ff38}\u{ff41}mple\u{3002}com").unwrap(),
+            "example.com"
+        );
+        assert_eq!(to_ascii("ex\u{ad}ample.com").unwrap(), "example.com");
+        assert_eq!(
+            to_unicode("M\u{fc}nchen.DE"),
+            ("m\u{fc}nchen.de".into(), Ok(()))
+        );
+        assert_eq!(to_unicode("Mu\u{308}nchen.de").0, "m\u{fc}nchen.de");
+        assert_eq!(to_ascii("m\u{fc}nchen.de").unwrap(), "xn--mnchen-3ya.de");
+        assert_eq!(to_unicode("xn--mnchen-3ya.de").0, "m\u{fc}nchen.de");
+    }
+
+    #[test]
+    fn test_processing() {
+        let transitional = Config::new().processing(Processing::Transitional);
+        assert_eq!(to_ascii("\u{3c2}.gr").unwrap(), "xn--3xa.gr");
+        assert_eq!(transitional.to_ascii("\u{3c2}.gr").unwrap(), "xn--4xa.gr");
+        assert_eq!(to_unicode("FA\u{1e9e}.de").0, "fa\u{df}.de");
+        assert_eq!(transitional.to_ascii("FA\u{1e9e}.de").unwrap(), "fass.de");
+
+        // Decoded labels are always checked with Nontransitional Processing.
+        assert_eq!(
+            transitional.to_unicode("xn--fa-hia.de"),
+            ("fa\u{df}.de".into(), Ok(()))
+        );
+    }
+
+    #[test]
+    fn test_validity() {
+        let error = |domain: &str| to_ascii(domain).map_err(|e| (e.kind(), e.label()));
+
+        assert_eq!(error("a.-b.c"), Err((ErrorKind::Hyphens, 1)));
+        assert_eq!(error("a.b-.c"), Err((ErrorKind::Hyphens, 1)));
+        assert_eq!(error("ab--c.d"), Err((ErrorKind::Hyphens, 0)));
+        assert!(Config::new().check_hyphens(false).to_ascii("ab--c").is_ok());
+        assert_eq!(
+            Config::new().check_hyphens(false).to_ascii("xn--ab"),
+            to_ascii("xn--ab")
+        );
+
+        assert_eq!(error("\u{301}a"), Err((ErrorKind::LeadingCombiningMark, 0)));
+        assert_eq!(error("a_b"), Err((ErrorKind::DisallowedCharacter('_'), 0)));
+        assert!(Config::new()
+            .use_std3_ascii_rules(false)
+            .to_ascii("a_b")
+            .is_ok());
+        assert_eq!(
+            error("a\u{ff3f}b"),
+            Err((ErrorKind::DisallowedCharacter('_'), 0))
+        );
+        assert_eq!(
+            error("a\u{e000}b"),
+            Err((ErrorKind::DisallowedCharacter('\u{e000}'), 0))
+        );
+
+        assert_eq!(error("xn--99999999999.b"), Err((ErrorKind::Punycode, 0)));
+        assert_eq!(error("xn--ab-.b"), Err((ErrorKind::Punycode, 0)));
+        assert_eq!(error("a.xn--a-xbb"), Err((ErrorKind::NotNormalized, 1)));
+    }
+
+    #[test]
+    fn test_errors_keep_label() {
+        let (unicode, result) = to_unicode("xn--99999999999.B\u{fc}cher.-");
+        assert_eq!(unicode, "xn--99999999999.b\u{fc}cher.-");
+        let error = result.unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::Punycode);
+        assert_eq!(error.label(), 0);
+    }
+
+    #[test]
+    fn test_joiners() {
+        // After a virama.
+        assert!(to_ascii("\u{915}\u{94d}\u{200d}\u{937}").is_ok());
+        // Between a dual joining and a right joining Arabic letter.
+        assert!(to_ascii("\u{628}\u{200c}\u{627}").is_ok());
+        assert!(to_ascii("\u{628}\u{64b}\u{200c}\u{627}").is_ok());
+        assert_eq!(
+            to_ascii("a\u{200c}b").unwrap_err().kind(),
+            ErrorKind::Joiners
+        );
+        assert_eq!(
+            to_ascii("\u{627}\u{200c}\u{628}").unwrap_err().kind(),
+            ErrorKind::Joiners
+        );
+        assert!(Config::new()
+            .check_joiners(false)
+            .to_ascii("a\u{200d}b")
+            .is_ok());
+    }
+
+    #[test]
+    fn test_bidi() {
+        assert!(to_ascii("\u{5e9}\u{5dc}\u{5d5}\u{5dd}.example").is_ok());
+        assert!(to_ascii("\u{5d0}1").is_ok());
+        assert!(to_ascii("\u{627}\u{661}").is_ok());
+        assert!(to_ascii("\u{627}1\u{661}").is_err());
+        // A label must end with a strong or numeric character.
+        assert_eq!(to_ascii("\u{5d0}-").unwrap_err().kind(), ErrorKind::Hyphens);
+        assert_eq!(
+            Config::new()
+                .check_hyphens(false)
+                .to_ascii("\u{5d0}-")
+                .unwrap_err()
+                .kind(),
+            ErrorKind::Bidi
+        );
+        // Labels starting with a digit are not allowed in a Bidi domain name.
+        assert_eq!(to_ascii("1a.\u{5d0}").unwrap_err().label(), 0);
+        assert!(to_ascii("1a.example").is_ok());
+        assert!(Config::new()
+            .check_bidi(false)
+            .to_ascii("1a.\u{5d0}")
+            .is_ok());
+    }
+
+    #[test]
+    fn test_dns_length() {
+        let label = "a".repeat(63);
+        assert!(to_ascii(&label).is_ok());
+        let long = "a".repeat(64);
+        assert_eq!(to_ascii(&long).unwrap_err().kind(), ErrorKind::LabelTooLong);
+        let domain = [label.as_str(); 4].join(".");
+        assert_eq!(domain.len(), 255);
+        assert_eq!(
+            to_ascii(&domain).unwrap_err().kind(),
+            ErrorKind::DomainTooLong
+        );
+        assert_eq!(to_ascii("").unwrap_err().kind(), ErrorKind::EmptyLabel);
+        assert_eq!(to_ascii("a..b").unwrap_err().label(), 1);
+        assert_eq!(to_ascii("example.").unwrap_err().label(), 1);
+
+        let lenient = Config::new().verify_dns_length(false);
+        assert_eq!(lenient.to_ascii("example.").unwrap(), "example.");
+        assert_eq!(lenient.to_ascii(&long).unwrap(), long);
+        assert_eq!(to_unicode("a..b"), ("a..b".into(), Ok(())));
+    }
+}
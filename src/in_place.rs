@@ -0,0 +1,341 @@
+// Copyright 2012-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Normalization of owned strings which reuses their allocation.
+
+use crate::form::Form;
+use crate::lookups::canonical_combining_class;
+use crate::quick_check::{quick_check_span, IsNormalized};
+use core::{iter, str};
+use tinyvec::TinyVec;
+
+#[cfg(not(feature = "std"))]
+use alloc::{string::String, vec::Vec};
+
+/// The gap opened in front of the unread text the first time a normalized
+/// segment outgrows the text it replaces. It doubles every time it runs out,
+/// so that the unread text is only moved a logarithmic number of times.
+const MIN_GAP: usize = 16;
+
+/// How much to grow the gap by when it is `needed` elements short, given the
+/// `spare` capacity: as much as the gap doubling calls for, but without
+/// reallocating unless the spare capacity is too small anyway.
+#[inline]
+fn grow_by(spare: usize, needed: usize, gap: usize) -> usize {
+    if needed <= spare {
+        needed.max(gap.min(spare))
+    } else {
+        needed.max(gap)
+    }
+}
+
+/// Normalize `s` from `boundary` onward within its own buffer.
+///
+/// The normalized text is written at `w` over the text already read, which
+/// ends at `r`. Where the quick check proves the text normalized, it is just
+/// moved down; the other segments are normalized one at a time through a
+/// small buffer. `w` can only catch up with `r` when decomposing makes the
+/// text longer, in which case a gap is inserted in front of the unread text.
+#[inline]
+#[allow(unsafe_code)]
+fn normalize_in_place(s: &mut String, form: Form) {
+    let boundary = quick_check_span(s, form);
+    if boundary == s.len() {
+        return;
+    }
+
+    let mut v = core::mem::replace(s, String::new()).into_bytes();
+    let mut segment: TinyVec<[u8; 32]> = TinyVec::new();
+    let mut gap = MIN_GAP;
+    let (mut w, mut r) = (boundary, boundary);
+    while r < v.len() {
+        // Safety: `v[r..]` is the unread end of the original string, which
+        // is only ever moved as a whole, and `r` is on a character boundary.
+        let unread = unsafe { str::from_utf8_unchecked(&v[r..]) };
+        let span = quick_check_span(unread, form);
+        // The quick check stops on a character that isn't known to be
+        // normalized, and the segment extends to the next boundary after it.
+        let end = unread[span..]
+            .char_indices()
+            .skip(1)
+            .find(|&(_, c)| form.is_boundary_before(c))
+            .map_or(unread.len(), |(i, _)| span + i);
+        segment.clear();
+        form.normalize_chars(unread[span..end].chars(), |c| {
+            let mut buf = [0; 4];
+            segment.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+        });
+
+        if w != r {
+            for i in r..r + span {
+                v[w + i - r] = v[i];
+            }
+        }
+        w += span;
+        r += end;
+
+        if w + segment.len() > r {
+            let extra = grow_by(v.capacity() - v.len(), w + segment.len() - r, gap);
+            v.splice(r..r, iter::repeat(0).take(extra));
+            r += extra;
+            gap = extra * 2;
+        }
+        v[w..w + segment.len()].copy_from_slice(&segment);
+        w += segment.len();
+    }
+    v.truncate(w);
+
+    // Safety: `v[..w]` is made of the original string's prefix, spans of its
+    // characters and the UTF-8 encoding of the normalized segments.
+    *s = unsafe { String::from_utf8_unchecked(v) };
+}
+
+/// Like `quick_check_span`, for a slice of `char`s.
+fn quick_check_span_chars(s: &[char], form: Form) -> usize {
+    let mut last_cc = 0u8;
+    let mut boundary = 0;
+    for (i, &ch) in s.iter().enumerate() {
+        let cc = canonical_combining_class(ch);
+        if last_cc > cc && cc != 0 {
+            return boundary;
+        }
+        match form.quick_check(ch) {
+            IsNormalized::Yes => {
+                if cc == 0 {
+                    boundary = i;
+                }
+            }
+            IsNormalized::No | IsNormalized::Maybe => return boundary,
+        }
+        last_cc = cc;
+    }
+    s.len()
+}
+
+/// Like `normalize_in_place`, for a vector of `char`s.
+#[inline]
+fn normalize_chars_in_place(s: &mut Vec<char>, form: Form) {
+    let boundary = quick_check_span_chars(s, form);
+    if boundary == s.len() {
+        return;
+    }
+
+    let mut segment: TinyVec<[char; 16]> = TinyVec::new();
+    let mut gap = MIN_GAP;
+    let (mut w, mut r) = (boundary, boundary);
+    while r < s.len() {
+        let unread = &s[r..];
+        let span = quick_check_span_chars(unread, form);
+        let end = unread[span..]
+            .iter()
+            .skip(1)
+            .position(|&c| form.is_boundary_before(c))
+            .map_or(unread.len(), |i| span + 1 + i);
+        segment.clear();
+        form.normalize_chars(unread[span..end].iter().cloned(), |c| segment.push(c));
+
+        if w != r {
+            for i in r..r + span {
+                s[w + i - r] = s[i];
+            }
+        }
+        w += span;
+        r += end;
+
+        if w + segment.len() > r {
+            let extra = grow_by(s.capacity() - s.len(), w + segment.len() - r, gap);
+            s.splice(r..r, iter::repeat('\0').take(extra));
+            r += extra;
+            gap = extra * 2;
+        }
+        s[w..w + segment.len()].copy_from_slice(&segment);
+        w += segment.len();
+    }
+    s.truncate(w);
+}
+
+/// Convert the string to Unicode Normalization Form C, reusing its
+/// allocation.
+///
+/// The longest prefix of `s` that is proven normalized by the quick check is
+/// left untouched, and only the remainder is rewritten, within the string's
+/// own buffer. It only reallocates if the normalized string outgrows the
+/// string's capacity. If `s` is already normalized, it is not modified at
+/// all.
+#[inline]
+pub fn normalize_nfc_in_place(s: &mut String) {
+    normalize_in_place(s, Form::Nfc)
+}
+
+/// Convert the string to Unicode Normalization Form KC, reusing its
+/// allocation.
+///
+/// See [`normalize_nfc_in_place`] for details.
+#[inline]
+pub fn normalize_nfkc_in_place(s: &mut String) {
+    normalize_in_place(s, Form::Nfkc)
+}
+
+/// Convert the string to Unicode Normalization Form D, reusing its
+/// allocation.
+///
+/// See [`normalize_nfc_in_place`] for details.
+#[inline]
+pub fn normalize_nfd_in_place(s: &mut String) {
+    normalize_in_place(s, Form::Nfd)
+}
+
+/// Convert the string to Unicode Normalization Form KD, reusing its
+/// allocation.
+///
+/// See [`normalize_nfc_in_place`] for details.
+#[inline]
+pub fn normalize_nfkd_in_place(s: &mut String) {
+    normalize_in_place(s, Form::Nfkd)
+}
+
+/// Convert the characters to Unicode Normalization Form C, reusing the
+/// vector's allocation.
+///
+/// The longest prefix of `s` that is proven normalized by the quick check is
+/// left untouched, and only the remainder is rewritten, within the vector's
+/// own buffer. It only reallocates if the normalized characters outgrow the
+/// vector's capacity.
+#[inline]
+pub fn normalize_nfc_chars_in_place(s: &mut Vec<char>) {
+    normalize_chars_in_place(s, Form::Nfc)
+}
+
+/// Convert the characters to Unicode Normalization Form KC, reusing the
+/// vector's allocation.
+///
+/// See [`normalize_nfc_chars_in_place`] for details.
+#[inline]
+pub fn normalize_nfkc_chars_in_place(s: &mut Vec<char>) {
+    normalize_chars_in_place(s, Form::Nfkc)
+}
+
+/// Convert the characters to Unicode Normalization Form D, reusing the
+/// vector's allocation.
+///
+/// See [`normalize_nfc_chars_in_place`] for details.
+#[inline]
+pub fn normalize_nfd_chars_in_place(s: &mut Vec<char>) {
+    normalize_chars_in_place(s, Form::Nfd)
+}
+
+/// Convert the characters to Unicode Normalization Form KD, reusing the
+/// vector's allocation.
+///
+/// See [`normalize_nfc_chars_in_place`] for details.
+#[inline]
+pub fn normalize_nfkd_chars_in_place(s: &mut Vec<char>) {
+    normalize_chars_in_place(s, Form::Nfkd)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        normalize_nfc_chars_in_place, normalize_nfc_in_place, normalize_nfd_in_place,
+        normalize_nfkd_chars_in_place, normalize_nfkd_in_place,
+    };
+    use crate::UnicodeNormalization;
+
+    #[cfg(not(feature = "std"))]
+    use alloc::{
+        string::{String, ToString},
+        vec::Vec,
+    };
+
+    #[test]
+    fn test_unchanged_keeps_allocation() {
+        let mut s = String::with_capacity(64);
+        s.push_str("Introducci\u{00f3}n ab\u{301}");
+        let ptr = s.as_ptr();
+        normalize_nfc_in_place(&mut s);
+        assert_eq!(s, "Introducci\u{00f3}n ab\u{301}");
+        assert_eq!(s.as_ptr(), ptr);
+        assert_eq!(s.capacity(), 64);
+    }
+
+    #[test]
+    fn test_normalizes_tail() {
+        let mut s = "Introduccio\u{0301}n a Unicode.pdf".to_string();
+        let ptr = s.as_ptr();
+        normalize_nfc_in_place(&mut s);
+        assert_eq!(s, "Introducci\u{00f3}n a Unicode.pdf");
+        // NFC made the string shorter, so it still fits.
+        assert_eq!(s.as_ptr(), ptr);
+
+        let mut s = "abc\u{1e0b}\u{323}".to_string();
+        normalize_nfd_in_place(&mut s);
+        assert_eq!(s, "abcd\u{323}\u{307}");
+
+        let mut s = "\u{2026}\u{2026}".to_string();
+        normalize_nfkd_in_place(&mut s);
+        assert_eq!(s, "......");
+
+        let mut s = "xa\u{300}\u{305}\u{315}\u{5ae}b".to_string();
+        normalize_nfc_in_place(&mut s);
+        assert_eq!(s, "x\u{e0}\u{5ae}\u{305}\u{315}b");
+    }
+
+    #[test]
+    fn test_unnormalized_from_start() {
+        // NFC doesn't make this any longer, so the allocation is reused.
+        let input = "e\u{301}\u{1e0b}\u{323}".repeat(100);
+        let mut s = input.clone();
+        let (ptr, capacity) = (s.as_ptr(), s.capacity());
+        normalize_nfc_in_place(&mut s);
+        assert_eq!(s, input.nfc().collect::<String>());
+        assert_eq!((s.as_ptr(), s.capacity()), (ptr, capacity));
+
+        let input = "\u{e9}\u{1e0b}\u{323}x\u{ac00}".repeat(100);
+        let mut s = input.clone();
+        normalize_nfd_in_place(&mut s);
+        assert_eq!(s, input.nfd().collect::<String>());
+        // The result fits in the capacity, so it still isn't reallocated.
+        let mut s = String::with_capacity(s.len());
+        s.push_str(&input);
+        let ptr = s.as_ptr();
+        normalize_nfd_in_place(&mut s);
+        assert_eq!(s, input.nfd().collect::<String>());
+        assert_eq!(s.as_ptr(), ptr);
+        let mut s = input.clone();
+        normalize_nfkd_in_place(&mut s);
+        assert_eq!(s, input.nfkd().collect::<String>());
+
+        let input: Vec<char> = "e\u{301}\u{1e0b}\u{323}".repeat(100).chars().collect();
+        let mut v = input.clone();
+        let ptr = v.as_ptr();
+        normalize_nfc_chars_in_place(&mut v);
+        assert_eq!(v, input.iter().cloned().nfc().collect::<Vec<_>>());
+        assert_eq!(v.as_ptr(), ptr);
+
+        let input: Vec<char> = "\u{e9}\u{2026}x".repeat(100).chars().collect();
+        let mut v = input.clone();
+        normalize_nfkd_chars_in_place(&mut v);
+        assert_eq!(v, input.iter().cloned().nfkd().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_chars() {
+        let mut v: Vec<char> = "xa\u{300}\u{305}\u{315}\u{5ae}b".chars().collect();
+        normalize_nfc_chars_in_place(&mut v);
+        assert_eq!(
+            v,
+            "x\u{e0}\u{5ae}\u{305}\u{315}b".chars().collect::<Vec<_>>()
+        );
+
+        let mut v: Vec<char> = "a\u{2026}".chars().collect();
+        normalize_nfkd_chars_in_place(&mut v);
+        assert_eq!(v, ['a', '.', '.', '.']);
+    }
+}
@@ -0,0 +1,637 @@
+// Copyright 2012-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Adapters for [`std::io::Read`] and [`std::io::Write`] which normalize the
+//! UTF-8 text streamed through them.
+//!
+//! The adapters only hold on to text from the last normalization boundary
+//! onward, and to UTF-8 sequences split across reads or writes, so large
+//! inputs can be normalized with a small, fixed amount of memory: about a
+//! kilobyte on top of the chunks read or written. To bound the buffered text
+//! regardless of input, it is first passed through the Stream-Safe Text
+//! Process ([UAX15-D4]): the output is the same as that of
+//! `s.stream_safe().nfc()`, which only differs from `s.nfc()` for input with
+//! more than 30 consecutive non-starters. A Combining Grapheme Joiner is also
+//! inserted in the runs of more than a kilobyte of starters which may all
+//! compose with the preceding character, such as Hangul vowel jamo, which no
+//! meaningful text contains.
+//!
+//! Input which is not well-formed UTF-8 results in an
+//! [`ErrorKind::InvalidData`](std::io::ErrorKind::InvalidData) error.
+//!
+//! ```rust
+//! use std::io::{Read, Write};
+//! use unicode_normalization::io::{NfcReader, NfdWriter};
+//!
+//! let mut nfc = String::new();
+//! NfcReader::new("Introduccio\u{301}n".as_bytes())
+//!     .read_to_string(&mut nfc)
+//!     .unwrap();
+//! assert_eq!(nfc, "Introducci\u{f3}n");
+//!
+//! let mut writer = NfdWriter::new(Vec::new());
+//! writer.write_all(nfc.as_bytes()).unwrap();
+//! let nfd = writer.finish().unwrap();
+//! assert_eq!(nfd, "Introduccio\u{301}n".as_bytes());
+//! ```
+//!
+//! [UAX15-D4]: https://www.unicode.org/reports/tr15/#UAX15-D4
+
+use crate::form::Form;
+use crate::stream::Incremental;
+use std::io::{self, Read, Write};
+use std::str;
+
+/// The number of bytes requested from the inner reader at a time.
+const CHUNK_SIZE: usize = 8 * 1024;
+
+fn invalid_utf8() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        "stream did not contain valid UTF-8",
+    )
+}
+
+struct Reader<R> {
+    inner: R,
+    normalizer: Incremental,
+    // Bytes read from `inner` that haven't been normalized yet. Between calls
+    // to `read`, this only holds the start of a split UTF-8 sequence.
+    input: Vec<u8>,
+    output: Vec<u8>,
+    output_pos: usize,
+    eof: bool,
+}
+
+impl<R: Read> Reader<R> {
+    fn new(inner: R, form: Form) -> Self {
+        Reader {
+            inner,
+            normalizer: Incremental::new(form, true),
+            input: Vec::new(),
+            output: Vec::new(),
+            output_pos: 0,
+            eof: false,
+        }
+    }
+
+    fn fill_output(&mut self) -> io::Result<()> {
+        self.output.clear();
+        self.output_pos = 0;
+
+        let start = self.input.len();
+        self.input.resize(start + CHUNK_SIZE, 0);
+        let n = match self.inner.read(&mut self.input[start..]) {
+            Ok(n) => n,
+            Err(e) => {
+                self.input.truncate(start);
+                return Err(e);
+            }
+        };
+        self.input.truncate(start + n);
+
+        if n == 0 {
+            if !self.input.is_empty() {
+                return Err(invalid_utf8());
+            }
+            self.eof = true;
+            self.normalizer.finish(&mut self.output);
+            return Ok(());
+        }
+
+        let valid_up_to = match str::from_utf8(&self.input) {
+            Ok(s) => s.len(),
+            Err(e) => match e.error_len() {
+                Some(_) => return Err(invalid_utf8()),
+                // The chunk ends in the middle of a sequence.
+                None => e.valid_up_to(),
+            },
+        };
+        if let Ok(s) = str::from_utf8(&self.input[..valid_up_to]) {
+            self.normalizer.push_str(s, &mut self.output);
+        }
+        self.input.drain(..valid_up_to);
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for Reader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        // A chunk may not produce any output if it doesn't complete a segment.
+        while self.output_pos == self.output.len() {
+            if self.eof {
+                return Ok(0);
+            }
+            self.fill_output()?;
+        }
+
+        let available = &self.output[self.output_pos..];
+        let n = available.len().min(buf.len());
+        buf[..n].copy_from_slice(&available[..n]);
+        self.output_pos += n;
+        Ok(n)
+    }
+}
+
+struct Writer<W: Write> {
+    // Only `None` once the writer has been finished.
+    inner: Option<W>,
+    normalizer: Incremental,
+    // The start of a UTF-8 sequence split across writes.
+    incomplete: Vec<u8>,
+    // Normalized output which hasn't been written to `inner` yet.
+    output: Vec<u8>,
+}
+
+impl<W: Write> Writer<W> {
+    fn new(inner: W, form: Form) -> Self {
+        Writer {
+            inner: Some(inner),
+            normalizer: Incremental::new(form, true),
+            incomplete: Vec::new(),
+            output: Vec::new(),
+        }
+    }
+
+    fn get_ref(&self) -> &W {
+        self.inner.as_ref().expect("writer used after finish")
+    }
+
+    fn get_mut(&mut self) -> &mut W {
+        self.inner.as_mut().expect("writer used after finish")
+    }
+
+    fn write_output(&mut self) -> io::Result<()> {
+        let inner = self.inner.as_mut().expect("writer used after finish");
+        while !self.output.is_empty() {
+            match inner.write(&self.output) {
+                Ok(0) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::WriteZero,
+                        "failed to write the normalized output",
+                    ));
+                }
+                Ok(n) => {
+                    self.output.drain(..n);
+                }
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+
+    /// Pass the UTF-8 text at the start of `buf` to the normalizer, keeping a
+    /// sequence split at its end for the next call, and return the number of
+    /// bytes consumed, which is less than `buf.len()` only when it is followed
+    /// by invalid UTF-8.
+    fn push_input(&mut self, buf: &[u8]) -> usize {
+        let mut consumed = 0;
+
+        // Complete a sequence split across writes, one byte at a time.
+        while !self.incomplete.is_empty() && consumed < buf.len() {
+            self.incomplete.push(buf[consumed]);
+            match str::from_utf8(&self.incomplete) {
+                Ok(s) => {
+                    self.normalizer.push_str(s, &mut self.output);
+                    self.incomplete.clear();
+                }
+                Err(e) => {
+                    if e.error_len().is_some() {
+                        self.incomplete.pop();
+                        return consumed;
+                    }
+                }
+            }
+            consumed += 1;
+        }
+        if !self.incomplete.is_empty() {
+            return consumed;
+        }
+
+        let rest = &buf[consumed..];
+        let (valid_up_to, invalid) = match str::from_utf8(rest) {
+            Ok(s) => (s.len(), false),
+            Err(e) => (e.valid_up_to(), e.error_len().is_some()),
+        };
+        if let Ok(s) = str::from_utf8(&rest[..valid_up_to]) {
+            self.normalizer.push_str(s, &mut self.output);
+        }
+        if invalid {
+            return consumed + valid_up_to;
+        }
+        self.incomplete.extend_from_slice(&rest[valid_up_to..]);
+        buf.len()
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        if !self.incomplete.is_empty() {
+            return Err(invalid_utf8());
+        }
+        self.normalizer.finish(&mut self.output);
+        self.write_output()?;
+        self.get_mut().flush()
+    }
+}
+
+impl<W: Write> Write for Writer<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // Pass on the output left by a failed write before accepting more
+        // input, so that an error here means nothing was consumed.
+        self.write_output()?;
+
+        let n = self.push_input(buf);
+        if n == 0 && !buf.is_empty() {
+            return Err(invalid_utf8());
+        }
+
+        // The input has been consumed, so it must be reported as written even
+        // if the inner writer fails: the output is kept, and the error comes
+        // up again on the next call to `write` or `flush`. Likewise, invalid
+        // UTF-8 after some valid input is only reported by the next call.
+        let _ = self.write_output();
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.write_output()?;
+        self.get_mut().flush()
+    }
+}
+
+impl<W: Write> Drop for Writer<W> {
+    fn drop(&mut self) {
+        // Like `BufWriter`, make a best effort to write out the remaining text.
+        if self.inner.is_some() {
+            let _ = self.finish();
+        }
+    }
+}
+
+macro_rules! normalizing_io {
+    (
+        $(#[$reader_attr:meta])* $reader:ident,
+        $(#[$writer_attr:meta])* $writer:ident,
+        $form:expr
+    ) => {
+        $(#[$reader_attr])*
+        pub struct $reader<R> {
+            inner: Reader<R>,
+        }
+
+        impl<R: Read> $reader<R> {
+            /// Create a new normalizing reader around `inner`.
+            #[inline]
+            pub fn new(inner: R) -> Self {
+                $reader {
+                    inner: Reader::new(inner, $form),
+                }
+            }
+
+            /// Gets a reference to the underlying reader.
+            #[inline]
+            pub fn get_ref(&self) -> &R {
+                &self.inner.inner
+            }
+
+            /// Gets a mutable reference to the underlying reader.
+            ///
+            /// Reading from it directly will corrupt the normalized stream.
+            #[inline]
+            pub fn get_mut(&mut self) -> &mut R {
+                &mut self.inner.inner
+            }
+
+            /// Unwraps this reader, returning the underlying reader.
+            ///
+            /// Any text that has been read but not yet returned is lost.
+            #[inline]
+            pub fn into_inner(self) -> R {
+                self.inner.inner
+            }
+        }
+
+        impl<R: Read> Read for $reader<R> {
+            #[inline]
+            fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+                self.inner.read(buf)
+            }
+        }
+
+        $(#[$writer_attr])*
+        pub struct $writer<W: Write> {
+            inner: Writer<W>,
+        }
+
+        impl<W: Write> $writer<W> {
+            /// Create a new normalizing writer around `inner`.
+            #[inline]
+            pub fn new(inner: W) -> Self {
+                $writer {
+                    inner: Writer::new(inner, $form),
+                }
+            }
+
+            /// Gets a reference to the underlying writer.
+            #[inline]
+            pub fn get_ref(&self) -> &W {
+                self.inner.get_ref()
+            }
+
+            /// Gets a mutable reference to the underlying writer.
+            ///
+            /// Writing to it directly will corrupt the normalized stream.
+            #[inline]
+            pub fn get_mut(&mut self) -> &mut W {
+                self.inner.get_mut()
+            }
+
+            /// Normalize and write the text buffered since the last
+            /// normalization boundary, flush, and return the underlying
+            /// writer.
+            ///
+            /// Unlike [`flush`](Write::flush), this marks the end of the text:
+            /// anything written afterwards would be normalized separately.
+            /// Dropping the writer also finishes it, but ignores any errors.
+            pub fn finish(mut self) -> io::Result<W> {
+                self.inner.finish()?;
+                Ok(self.inner.inner.take().expect("writer used after finish"))
+            }
+        }
+
+        impl<W: Write> Write for $writer<W> {
+            #[inline]
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                self.inner.write(buf)
+            }
+
+            /// Write out all the text normalized so far and flush the
+            /// underlying writer.
+            ///
+            /// Text since the last normalization boundary is kept back, since
+            /// it may still change depending on what is written next.
+            #[inline]
+            fn flush(&mut self) -> io::Result<()> {
+                self.inner.flush()
+            }
+        }
+    };
+}
+
+normalizing_io! {
+    /// A reader which converts the UTF-8 text read from an inner reader to
+    /// Unicode Normalization Form C.
+    ///
+    /// See the [module documentation](self) for details.
+    NfcReader,
+    /// A writer which converts the UTF-8 text written to it to Unicode
+    /// Normalization Form C before passing it on to an inner writer.
+    ///
+    /// See the [module documentation](self) for details.
+    NfcWriter,
+    Form::Nfc
+}
+
+normalizing_io! {
+    /// A reader which converts the UTF-8 text read from an inner reader to
+    /// Unicode Normalization Form KC.
+    ///
+    /// See the [module documentation](self) for details.
+    NfkcReader,
+    /// A writer which converts the UTF-8 text written to it to Unicode
+    /// Normalization Form KC before passing it on to an inner writer.
+    ///
+    /// See the [module documentation](self) for details.
+    NfkcWriter,
+    Form::Nfkc
+}
+
+normalizing_io! {
+    /// A reader which converts the UTF-8 text read from an inner reader to
+    /// Unicode Normalization Form D.
+    ///
+    /// See the [module documentation](self) for details.
+    NfdReader,
+    /// A writer which converts the UTF-8 text written to it to Unicode
+    /// Normalization Form D before passing it on to an inner writer.
+    ///
+    /// See the [module documentation](self) for details.
+    NfdWriter,
+    Form::Nfd
+}
+
+normalizing_io! {
+    /// A reader which converts the UTF-8 text read from an inner reader to
+    /// Unicode Normalization Form KD.
+    ///
+    /// See the [module documentation](self) for details.
+    NfkdReader,
+    /// A writer which converts the UTF-8 text written to it to Unicode
+    /// Normalization Form KD before passing it on to an inner writer.
+    ///
+    /// See the [module documentation](self) for details.
+    NfkdWriter,
+    Form::Nfkd
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{NfcReader, NfcWriter, NfkdReader, NfkdWriter};
+    use crate::UnicodeNormalization;
+    use std::io::{self, Read, Write};
+
+    /// A reader which returns at most `n` bytes at a time.
+    struct Trickle<'a> {
+        data: &'a [u8],
+        n: usize,
+    }
+
+    impl<'a> Read for Trickle<'a> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let n = self.n.min(buf.len()).min(self.data.len());
+            buf[..n].copy_from_slice(&self.data[..n]);
+            self.data = &self.data[n..];
+            Ok(n)
+        }
+    }
+
+    const INPUT: &str = "Introduccio\u{0301}n \u{1e0b}\u{323}\u{2026} a\u{300}\u{305}\u{315}\u{5ae}b \u{1100}\u{1161}\u{11a8}";
+
+    #[test]
+    fn test_reader_split_sequences() {
+        for n in 1..8 {
+            let mut nfc = String::new();
+            let reader = Trickle {
+                data: INPUT.as_bytes(),
+                n,
+            };
+            NfcReader::new(reader).read_to_string(&mut nfc).unwrap();
+            assert_eq!(nfc, INPUT.nfc().collect::<String>());
+
+            let mut nfkd = String::new();
+            let reader = Trickle {
+                data: INPUT.as_bytes(),
+                n,
+            };
+            NfkdReader::new(reader).read_to_string(&mut nfkd).unwrap();
+            assert_eq!(nfkd, INPUT.nfkd().collect::<String>());
+        }
+    }
+
+    #[test]
+    fn test_writer_split_sequences() {
+        for n in 1..8 {
+            let mut writer = NfcWriter::new(Vec::new());
+            for chunk in INPUT.as_bytes().chunks(n) {
+                writer.write_all(chunk).unwrap();
+            }
+            let nfc = writer.finish().unwrap();
+            assert_eq!(nfc, INPUT.nfc().collect::<String>().as_bytes());
+
+            let mut nfkd = Vec::new();
+            {
+                let mut writer = NfkdWriter::new(&mut nfkd);
+                for chunk in INPUT.as_bytes().chunks(n) {
+                    writer.write_all(chunk).unwrap();
+                }
+            }
+            assert_eq!(nfkd, INPUT.nfkd().collect::<String>().as_bytes());
+        }
+    }
+
+    #[test]
+    fn test_invalid_utf8() {
+        let mut s = String::new();
+        let err = NfcReader::new(&b"abc\xff"[..])
+            .read_to_string(&mut s)
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        let err = NfcReader::new(&b"abc\xe2\x80"[..])
+            .read_to_string(&mut s)
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        let mut writer = NfcWriter::new(Vec::new());
+        writer.write_all(b"abc\xe2\x80").unwrap();
+        assert_eq!(
+            writer.finish().unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+
+        let mut writer = NfcWriter::new(Vec::new());
+        writer.write_all(b"abc\xe2").unwrap();
+        assert_eq!(
+            writer.write_all(b"a").unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+
+        // The valid text before invalid data is consumed first, including the
+        // end of a sequence split across writes.
+        let mut writer = NfcWriter::new(Vec::new());
+        assert_eq!(writer.write(b"abc\xff").unwrap(), 3);
+        assert_eq!(
+            writer.write(b"\xff").unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+        assert_eq!(writer.write(b"\xe2\x80").unwrap(), 2);
+        assert_eq!(writer.write(b"\xa6d\xff").unwrap(), 2);
+        assert_eq!(
+            writer.write(b"\xff").unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+        assert_eq!(writer.finish().unwrap(), "abc\u{2026}d".as_bytes());
+    }
+
+    /// A writer which fails with `WouldBlock` on its first `failures` writes.
+    struct Flaky {
+        data: Vec<u8>,
+        failures: usize,
+    }
+
+    impl Write for Flaky {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            if self.failures > 0 {
+                self.failures -= 1;
+                return Err(io::ErrorKind::WouldBlock.into());
+            }
+            self.data.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_writer_inner_error() {
+        let mut writer = NfcWriter::new(Flaky {
+            data: Vec::new(),
+            failures: 2,
+        });
+        // The input is accepted even though passing it on fails.
+        assert_eq!(writer.write(b"Introduccio").unwrap(), 11);
+        // The pending output fails to be written again, and the new input is
+        // rejected.
+        assert_eq!(
+            writer.write(b"\xcc\x81n").unwrap_err().kind(),
+            io::ErrorKind::WouldBlock
+        );
+        assert_eq!(writer.write(b"\xcc\x81n").unwrap(), 3);
+        let nfc = writer.finish().unwrap().data;
+        assert_eq!(nfc, "Introducci\u{f3}n".as_bytes());
+
+        let mut writer = NfcWriter::new(Flaky {
+            data: Vec::new(),
+            failures: 2,
+        });
+        writer.write_all(b"abc ").unwrap();
+        assert_eq!(
+            writer.flush().unwrap_err().kind(),
+            io::ErrorKind::WouldBlock
+        );
+        writer.flush().unwrap();
+        assert_eq!(writer.get_ref().data, b"abc");
+        assert_eq!(writer.finish().unwrap().data, b"abc ");
+    }
+
+    #[test]
+    fn test_composing_starters_bound_buffer() {
+        let input = "\u{1100}".to_string() + &"\u{1161}".repeat(10_000);
+        let mut writer = NfcWriter::new(Vec::new());
+        writer.write_all(input.as_bytes()).unwrap();
+        writer.flush().unwrap();
+        assert!(writer.get_ref().len() > input.len() - 2000);
+        let nfc = String::from_utf8(writer.finish().unwrap()).unwrap();
+        assert!(crate::is_nfc(&nfc));
+        assert_eq!(nfc.replace('\u{34f}', ""), input.nfc().collect::<String>());
+    }
+
+    #[test]
+    fn test_stream_safe_bounds_buffer() {
+        let input = "a".to_string() + &"\u{316}".repeat(10_000);
+        let mut writer = NfcWriter::new(Vec::new());
+        writer.write_all(input.as_bytes()).unwrap();
+        writer.flush().unwrap();
+        // Everything but the last few non-starters has been written out.
+        assert!(writer.get_ref().len() > input.len() - 100);
+        let nfc = writer.finish().unwrap();
+        assert_eq!(
+            nfc,
+            input
+                .chars()
+                .stream_safe()
+                .nfc()
+                .collect::<String>()
+                .as_bytes()
+        );
+    }
+}
@@ -52,27 +52,70 @@ extern crate core;
 
 extern crate tinyvec;
 
+pub use crate::casefold::{
+    canonical_caseless_eq, compatibility_caseless_eq, CaseFolding, CaseFolds,
+};
+pub use crate::cow::{nfc_cow, nfd_cow, nfkc_cow, nfkd_cow};
 pub use crate::decompose::Decompositions;
+pub use crate::equivalence::{
+    canonically_equivalent, compatibility_equivalent, CanonicalStr, CompatibilityStr,
+};
+pub use crate::in_place::{
+    normalize_nfc_chars_in_place, normalize_nfc_in_place, normalize_nfd_chars_in_place,
+    normalize_nfd_in_place, normalize_nfkc_chars_in_place, normalize_nfkc_in_place,
+    normalize_nfkd_chars_in_place, normalize_nfkd_in_place,
+};
 pub use crate::quick_check::{
     is_nfc, is_nfc_quick, is_nfc_stream_safe, is_nfc_stream_safe_quick, is_nfd, is_nfd_quick,
-    is_nfd_stream_safe, is_nfd_stream_safe_quick, is_nfkc, is_nfkc_quick, is_nfkd, is_nfkd_quick,
-    IsNormalized,
+    is_nfd_stream_safe, is_nfd_stream_safe_quick, is_nfkc, is_nfkc_casefold,
+    is_nfkc_casefold_quick, is_nfkc_quick, is_nfkd, is_nfkd_quick, nfc_quick_span, nfd_quick_span,
+    nfkc_quick_span, nfkd_quick_span, IsNormalized,
 };
 pub use crate::recompose::Recompositions;
 pub use crate::replace::Replacements;
+pub use crate::search::{SearchFold, SearchFolds};
+pub use crate::stream::Normalizer;
 pub use crate::stream_safe::StreamSafe;
+pub use crate::string::{
+    concat_nfc, concat_nfd, concat_nfkc, concat_nfkd, NfcStr, NfcString, NfdStr, NfdString,
+    NfkcStr, NfkcString, NfkdStr, NfkdString,
+};
+pub use crate::strip_marks::{MarkStripping, StripMarks};
 pub use crate::tables::UNICODE_VERSION;
+pub use crate::writer::NormalizingWriter;
 use core::{option, str::Chars};
 
+mod casefold;
+mod cow;
 mod decompose;
+mod equivalence;
+mod form;
+pub mod identifier;
+pub mod idna;
+mod in_place;
+#[cfg(feature = "std")]
+pub mod io;
 mod lookups;
 mod normalize;
 mod perfect_hash;
+pub mod precis;
+mod properties;
 mod quick_check;
 mod recompose;
 mod replace;
+mod search;
+pub mod security;
+#[cfg(feature = "serde")]
+pub mod serde;
+mod stream;
 mod stream_safe;
+mod string;
+pub mod stringprep;
+mod strip_marks;
 mod tables;
+pub mod utf16;
+pub mod utf8;
+mod writer;
 
 #[doc(hidden)]
 pub mod __test_api;
@@ -113,6 +156,27 @@ pub trait UnicodeNormalization<I: Iterator<Item = char>> {
     /// (compatibility decomposition followed by canonical composition).
     fn nfkc(self) -> Recompositions<I>;
 
+    /// An Iterator over the string mapped by `NFKC_Casefold` (NFKC_CF), which
+    /// applies NFKC, full case folding and the removal of
+    /// `Default_Ignorable_Code_Point`s, for caseless matching of identifiers.
+    /// See [UAX #44](https://www.unicode.org/reports/tr44/#NFKC_Casefold) and
+    /// [UAX #31](https://www.unicode.org/reports/tr31/#NFKC_Modifications).
+    fn nfkc_casefold(self) -> Recompositions<I>;
+
+    /// An Iterator over the string in the variant of Normalization Form D
+    /// which HFS+ uses for file names. It follows Unicode 3.2 and leaves the
+    /// characters in the ranges U+2000 to U+2FFF, U+F900 to U+FAFF and
+    /// U+2F800 to U+2FAFF undecomposed. See
+    /// [Apple Technical Note TN1150](https://developer.apple.com/library/archive/technotes/tn/tn1150.html#UnicodeSubtleties).
+    fn hfs_nfd(self) -> Decompositions<I>;
+
+    /// An Iterator over the string in the variant of Normalization Form C
+    /// which the Finder uses for the file names of HFS+ volumes: the HFS+
+    /// variant of Normalization Form D followed by canonical composition,
+    /// which never composes into a character that
+    /// [`hfs_nfd`](UnicodeNormalization::hfs_nfd) leaves undecomposed.
+    fn hfs_nfc(self) -> Recompositions<I>;
+
     /// A transformation which replaces [CJK Compatibility Ideograph] codepoints
     /// with normal forms using [Standardized Variation Sequences]. This is not
     /// part of the canonical or compatibility decomposition algorithms, but
@@ -133,6 +197,13 @@ pub trait UnicodeNormalization<I: Iterator<Item = char>> {
     ///
     /// [UAX15-D4]: https://www.unicode.org/reports/tr15/#UAX15-D4
     fn stream_safe(self) -> StreamSafe<I>;
+
+    /// An Iterator over the string in Unicode Normalization Form C without
+    /// its combining marks, for accent-insensitive matching. The string is
+    /// decomposed first, so `é` becomes `e`. See [`StripMarks`] to keep the
+    /// marks which are significant in their script or to keep the result
+    /// decomposed.
+    fn strip_marks(self) -> StripMarks<I>;
 }
 
 impl<'a> UnicodeNormalization<Chars<'a>> for &'a str {
@@ -156,6 +227,21 @@ impl<'a> UnicodeNormalization<Chars<'a>> for &'a str {
         Recompositions::new_compatible(self.chars())
     }
 
+    #[inline]
+    fn nfkc_casefold(self) -> Recompositions<Chars<'a>> {
+        Recompositions::new_nfkc_casefold(self.chars())
+    }
+
+    #[inline]
+    fn hfs_nfd(self) -> Decompositions<Chars<'a>> {
+        Decompositions::new_hfs(self.chars())
+    }
+
+    #[inline]
+    fn hfs_nfc(self) -> Recompositions<Chars<'a>> {
+        Recompositions::new_hfs(self.chars())
+    }
+
     #[inline]
     fn cjk_compat_variants(self) -> Replacements<Chars<'a>> {
         Replacements::new_cjk_compat_variants(self.chars())
@@ -165,6 +251,11 @@ impl<'a> UnicodeNormalization<Chars<'a>> for &'a str {
     fn stream_safe(self) -> StreamSafe<Chars<'a>> {
         StreamSafe::new(self.chars())
     }
+
+    #[inline]
+    fn strip_marks(self) -> StripMarks<Chars<'a>> {
+        StripMarks::new_nfc(self.chars(), MarkStripping::All)
+    }
 }
 
 impl UnicodeNormalization<option::IntoIter<char>> for char {
@@ -188,6 +279,21 @@ impl UnicodeNormalization<option::IntoIter<char>> for char {
         Recompositions::new_compatible(Some(self).into_iter())
     }
 
+    #[inline]
+    fn nfkc_casefold(self) -> Recompositions<option::IntoIter<char>> {
+        Recompositions::new_nfkc_casefold(Some(self).into_iter())
+    }
+
+    #[inline]
+    fn hfs_nfd(self) -> Decompositions<option::IntoIter<char>> {
+        Decompositions::new_hfs(Some(self).into_iter())
+    }
+
+    #[inline]
+    fn hfs_nfc(self) -> Recompositions<option::IntoIter<char>> {
+        Recompositions::new_hfs(Some(self).into_iter())
+    }
+
     #[inline]
     fn cjk_compat_variants(self) -> Replacements<option::IntoIter<char>> {
         Replacements::new_cjk_compat_variants(Some(self).into_iter())
@@ -197,6 +303,11 @@ impl UnicodeNormalization<option::IntoIter<char>> for char {
     fn stream_safe(self) -> StreamSafe<option::IntoIter<char>> {
         StreamSafe::new(Some(self).into_iter())
     }
+
+    #[inline]
+    fn strip_marks(self) -> StripMarks<option::IntoIter<char>> {
+        StripMarks::new_nfc(Some(self).into_iter(), MarkStripping::All)
+    }
 }
 
 impl<I: Iterator<Item = char>> UnicodeNormalization<I> for I {
@@ -220,6 +331,21 @@ impl<I: Iterator<Item = char>> UnicodeNormalization<I> for I {
         Recompositions::new_compatible(self)
     }
 
+    #[inline]
+    fn nfkc_casefold(self) -> Recompositions<I> {
+        Recompositions::new_nfkc_casefold(self)
+    }
+
+    #[inline]
+    fn hfs_nfd(self) -> Decompositions<I> {
+        Decompositions::new_hfs(self)
+    }
+
+    #[inline]
+    fn hfs_nfc(self) -> Recompositions<I> {
+        Recompositions::new_hfs(self)
+    }
+
     #[inline]
     fn cjk_compat_variants(self) -> Replacements<I> {
         Replacements::new_cjk_compat_variants(self)
@@ -229,4 +355,9 @@ impl<I: Iterator<Item = char>> UnicodeNormalization<I> for I {
     fn stream_safe(self) -> StreamSafe<I> {
         StreamSafe::new(self)
     }
+
+    #[inline]
+    fn strip_marks(self) -> StripMarks<I> {
+        StripMarks::new_nfc(self, MarkStripping::All)
+    }
 }
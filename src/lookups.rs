@@ -11,7 +11,9 @@
 //! Lookups of unicode properties using minimal perfect hashing.
 
 use crate::perfect_hash::mph_lookup;
+use crate::properties::{BidiClass, DerivedProperty, JoiningType};
 use crate::tables::*;
+use core::cmp::Ordering;
 
 /// Look up the canonical combining class for a codepoint.
 ///
@@ -78,6 +80,218 @@ pub(crate) fn cjk_compat_variants_fully_decomposed(c: char) -> Option<&'static [
     .map(|(start, len)| &CJK_COMPAT_VARIANTS_DECOMPOSED_CHARS[start as usize..][..len as usize])
 }
 
+/// Look up the `NFKC_Casefold` mapping of a character, which may be empty.
+pub(crate) fn nfkc_casefold_mapped(c: char) -> Option<&'static [char]> {
+    if is_nfkc_casefold_removed(c) {
+        return Some(&[]);
+    }
+    mph_lookup(
+        c.into(),
+        NFKC_CASEFOLD_SALT,
+        NFKC_CASEFOLD_KV,
+        pair_lookup_fk,
+        pair_lookup_fv_opt,
+        None,
+    )
+    .map(|(start, len)| &NFKC_CASEFOLD_CHARS[start as usize..][..len as usize])
+}
+
+/// Look up the simple case folding of a character (statuses C and S in
+/// `CaseFolding.txt`).
+pub(crate) fn case_folding_simple(c: char) -> Option<char> {
+    mph_lookup(
+        c.into(),
+        CASE_FOLDING_SIMPLE_SALT,
+        CASE_FOLDING_SIMPLE_KV,
+        pair_lookup_fk,
+        pair_lookup_fv_opt,
+        None,
+    )
+}
+
+/// Look up the full case folding of a character where it differs from the
+/// simple one (status F in `CaseFolding.txt`).
+pub(crate) fn case_folding_full(c: char) -> Option<&'static [char]> {
+    mph_lookup(
+        c.into(),
+        CASE_FOLDING_FULL_SALT,
+        CASE_FOLDING_FULL_KV,
+        pair_lookup_fk,
+        pair_lookup_fv_opt,
+        None,
+    )
+    .map(|(start, len)| &CASE_FOLDING_FULL_CHARS[start as usize..][..len as usize])
+}
+
+/// Return whether the given character can start an identifier
+/// (`XID_Start`).
+pub fn is_xid_start(c: char) -> bool {
+    bsearch_range_table(c, XID_START)
+}
+
+/// Return whether the given character can continue an identifier
+/// (`XID_Continue`).
+pub fn is_xid_continue(c: char) -> bool {
+    bsearch_range_table(c, XID_CONTINUE)
+}
+
+/// Look up the mapping of a character with the status `mapped` in the UTS #46
+/// IDNA Mapping Table.
+pub(crate) fn idna_mapped(c: char) -> Option<&'static [char]> {
+    mph_lookup(
+        c.into(),
+        IDNA_MAPPED_SALT,
+        IDNA_MAPPED_KV,
+        pair_lookup_fk,
+        pair_lookup_fv_opt,
+        None,
+    )
+    .map(|(start, len)| &IDNA_MAPPED_CHARS[start as usize..][..len as usize])
+}
+
+/// Return whether a character has the status `valid` in the UTS #46 IDNA
+/// Mapping Table.
+pub(crate) fn is_idna_valid(c: char) -> bool {
+    bsearch_range_table(c, IDNA_VALID)
+}
+
+/// Look up the `Bidi_Class` of a character.
+pub(crate) fn bidi_class(c: char) -> BidiClass {
+    bsearch_range_value_table(c, BIDI_CLASS).unwrap_or(BidiClass::L)
+}
+
+/// Look up the `Joining_Type` of a character, or `None` if it is
+/// `Non_Joining` or `Join_Causing`.
+pub(crate) fn joining_type(c: char) -> Option<JoiningType> {
+    bsearch_range_value_table(c, JOINING_TYPE)
+}
+
+/// Look up the derived property of a character in the PRECIS framework.
+pub(crate) fn precis_property(c: char) -> DerivedProperty {
+    bsearch_range_value_table(c, PRECIS_PROPERTY).unwrap_or(DerivedProperty::Unassigned)
+}
+
+/// Look up the `<wide>` or `<narrow>` decomposition of a character.
+pub(crate) fn width_mapping(c: char) -> Option<char> {
+    mph_lookup(
+        c.into(),
+        WIDTH_MAPPING_SALT,
+        WIDTH_MAPPING_KV,
+        pair_lookup_fk,
+        pair_lookup_fv_opt,
+        None,
+    )
+}
+
+/// Look up the simple lowercase mapping of a character in `UnicodeData.txt`.
+pub(crate) fn lowercase(c: char) -> Option<char> {
+    mph_lookup(
+        c.into(),
+        LOWERCASE_SALT,
+        LOWERCASE_KV,
+        pair_lookup_fk,
+        pair_lookup_fv_opt,
+        None,
+    )
+}
+
+/// Return whether a character is `Cased`.
+pub(crate) fn is_cased(c: char) -> bool {
+    bsearch_range_table(c, CASED)
+}
+
+/// Return whether a character is `Case_Ignorable`.
+pub(crate) fn is_case_ignorable(c: char) -> bool {
+    bsearch_range_table(c, CASE_IGNORABLE)
+}
+
+pub(crate) fn is_greek(c: char) -> bool {
+    script(c) == SCRIPT_GREEK
+}
+
+pub(crate) fn is_hebrew(c: char) -> bool {
+    script(c) == SCRIPT_HEBREW
+}
+
+pub(crate) fn is_hiragana_katakana_or_han(c: char) -> bool {
+    let script = script(c);
+    script == SCRIPT_HIRAGANA || script == SCRIPT_KATAKANA || script == SCRIPT_HAN
+}
+
+/// Look up the `Script` of a character, as an index in the script sets of
+/// the `security` module.
+pub(crate) fn script(c: char) -> u8 {
+    bsearch_range_value_table(c, SCRIPT).unwrap_or(SCRIPT_UNKNOWN)
+}
+
+/// Look up the `Script_Extensions` of a character as a script set, for the
+/// characters where it isn't just their `Script`.
+pub(crate) fn script_extensions(c: char) -> Option<[u64; SCRIPT_WORDS]> {
+    bsearch_range_value_table(c, SCRIPT_EXTENSIONS)
+}
+
+/// Return whether a character has the `Identifier_Status` Allowed of
+/// UTS #39.
+pub(crate) fn is_identifier_allowed(c: char) -> bool {
+    bsearch_range_table(c, IDENTIFIER_ALLOWED)
+}
+
+/// Look up the prototype of a character in `confusables.txt`.
+pub(crate) fn confusable_prototype(c: char) -> Option<&'static [char]> {
+    mph_lookup(
+        c.into(),
+        CONFUSABLE_PROTOTYPE_SALT,
+        CONFUSABLE_PROTOTYPE_KV,
+        pair_lookup_fk,
+        pair_lookup_fv_opt,
+        None,
+    )
+    .map(|(start, len)| &CONFUSABLE_PROTOTYPE_CHARS[start as usize..][..len as usize])
+}
+
+/// Return whether a character is `Default_Ignorable_Code_Point`.
+pub(crate) fn is_default_ignorable(c: char) -> bool {
+    bsearch_range_table(c, DEFAULT_IGNORABLE)
+}
+
+/// Return whether a character is unassigned in Unicode 3.2 (table A.1 of
+/// RFC 3454).
+pub(crate) fn is_stringprep_unassigned(c: char) -> bool {
+    bsearch_range_table(c, STRINGPREP_UNASSIGNED)
+}
+
+/// Look up the case folding of a character for use with NFKC (table B.2 of
+/// RFC 3454).
+pub(crate) fn stringprep_case_fold(c: char) -> Option<&'static [char]> {
+    mph_lookup(
+        c.into(),
+        STRINGPREP_CASE_FOLD_SALT,
+        STRINGPREP_CASE_FOLD_KV,
+        pair_lookup_fk,
+        pair_lookup_fv_opt,
+        None,
+    )
+    .map(|(start, len)| &STRINGPREP_CASE_FOLD_CHARS[start as usize..][..len as usize])
+}
+
+/// Return whether a character is in one of the tables C.2.2 and C.3 to C.9
+/// of RFC 3454, which both Nameprep and SASLprep prohibit.
+pub(crate) fn is_stringprep_prohibited(c: char) -> bool {
+    bsearch_range_table(c, STRINGPREP_PROHIBITED)
+}
+
+/// Return whether a character has the bidirectional property R or AL in
+/// Unicode 3.2 (table D.1 of RFC 3454).
+pub(crate) fn is_stringprep_rand_al_cat(c: char) -> bool {
+    bsearch_range_table(c, STRINGPREP_RAND_AL_CAT)
+}
+
+/// Return whether a character has the bidirectional property L in
+/// Unicode 3.2 (table D.2 of RFC 3454).
+pub(crate) fn is_stringprep_l_cat(c: char) -> bool {
+    bsearch_range_table(c, STRINGPREP_L_CAT)
+}
+
 /// Return whether the given character is a combining mark (`General_Category=Mark`)
 pub fn is_combining_mark(c: char) -> bool {
     mph_lookup(
@@ -101,6 +315,38 @@ pub fn stream_safe_trailing_nonstarters(c: char) -> usize {
     ) as usize
 }
 
+/// Look up a character in a sorted table of disjoint inclusive ranges.
+#[inline]
+fn bsearch_range_table(c: char, r: &[(char, char)]) -> bool {
+    r.binary_search_by(|&(low, high)| {
+        if c < low {
+            Ordering::Greater
+        } else if c > high {
+            Ordering::Less
+        } else {
+            Ordering::Equal
+        }
+    })
+    .is_ok()
+}
+
+/// Look up the value of a character in a sorted table of disjoint inclusive
+/// ranges.
+#[inline]
+fn bsearch_range_value_table<T: Copy>(c: char, r: &[(char, char, T)]) -> Option<T> {
+    r.binary_search_by(|&(low, high, _)| {
+        if c < low {
+            Ordering::Greater
+        } else if c > high {
+            Ordering::Less
+        } else {
+            Ordering::Equal
+        }
+    })
+    .ok()
+    .map(|i| r[i].2)
+}
+
 /// Extract the key in a 24 bit key and 8 bit value packed in a u32.
 #[inline]
 fn u8_lookup_fk(kv: u32) -> u32 {
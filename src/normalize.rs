@@ -10,10 +10,12 @@
 
 //! Functions for computing canonical and compatible decompositions for Unicode characters.
 use crate::lookups::{
-    canonical_fully_decomposed, cjk_compat_variants_fully_decomposed,
-    compatibility_fully_decomposed, composition_table,
+    canonical_combining_class, canonical_fully_decomposed, cjk_compat_variants_fully_decomposed,
+    compatibility_fully_decomposed, composition_table, is_stringprep_unassigned,
+    nfkc_casefold_mapped,
 };
 
+use crate::decompose::YPOGEGRAMMENI;
 use core::char;
 
 /// Compute canonical Unicode decomposition for character.
@@ -37,6 +39,35 @@ pub fn decompose_compatible<F: FnMut(char)>(c: char, emit_char: F) {
     decompose(c, decompose_char, emit_char)
 }
 
+/// Compute the canonical decomposition of the `NFKC_Casefold` mapping of a
+/// character, as defined in
+/// [Unicode Standard Annex #44](https://www.unicode.org/reports/tr44/#NFKC_Casefold).
+#[inline]
+pub(crate) fn decompose_nfkc_casefold<F: FnMut(char)>(c: char, mut emit_char: F) {
+    if c <= '\x7f' {
+        emit_char(c.to_ascii_lowercase());
+        return;
+    }
+
+    // `toNFKC_Casefold` maps the canonical decomposition of its input, which
+    // only makes a difference for U+0345 as it is reordered before mapping.
+    // Leave it to `Decompositions` to fold it once it has been reordered.
+    decompose_canonical(c, |d| {
+        if d == YPOGEGRAMMENI {
+            emit_char(d);
+            return;
+        }
+        match nfkc_casefold_mapped(d) {
+            Some(mapped) => {
+                for &m in mapped {
+                    decompose_canonical(m, &mut emit_char);
+                }
+            }
+            None => emit_char(d),
+        }
+    })
+}
+
 /// Compute standard-variation decomposition for character.
 ///
 /// [Standardized Variation Sequences] are used instead of the standard canonical
@@ -71,6 +102,48 @@ where
     emit_char(c);
 }
 
+/// Return whether the HFS+ variant of NFD leaves a character undecomposed:
+/// the General Punctuation to CJK Symbols blocks (U+2000 to U+2FFF), the CJK
+/// compatibility ideographs (U+F900 to U+FAFF and U+2F800 to U+2FAFF), and
+/// the characters which were unassigned in Unicode 3.2, the version of the
+/// HFS+ tables. See [Apple Technical Note TN1150](https://developer.apple.com/library/archive/technotes/tn/tn1150.html#UnicodeSubtleties).
+#[inline]
+pub(crate) fn is_hfs_excluded(c: char) -> bool {
+    ('\u{2000}'..='\u{2FFF}').contains(&c)
+        || ('\u{F900}'..='\u{FAFF}').contains(&c)
+        || ('\u{2F800}'..='\u{2FAFF}').contains(&c)
+        || (c > '\x7f' && is_stringprep_unassigned(c))
+}
+
+/// Compute the decomposition of a character used by HFS+ for file names,
+/// which is its canonical decomposition unless [`is_hfs_excluded`].
+#[inline]
+pub(crate) fn decompose_hfs<F: FnMut(char)>(c: char, mut emit_char: F) {
+    if is_hfs_excluded(c) {
+        emit_char(c);
+        return;
+    }
+    decompose_canonical(c, emit_char)
+}
+
+/// Look up the canonical combining class of a character in the Unicode 3.2
+/// tables of HFS+, where the characters assigned later are starters.
+#[inline]
+pub(crate) fn hfs_combining_class(c: char) -> u8 {
+    match canonical_combining_class(c) {
+        0 => 0,
+        _ if is_stringprep_unassigned(c) => 0,
+        class => class,
+    }
+}
+
+/// Compose two characters for the HFS+ variant of NFC, which never composes
+/// them into a character that HFS+ doesn't decompose.
+#[inline]
+pub(crate) fn compose_hfs(a: char, b: char) -> Option<char> {
+    compose(a, b).filter(|&c| !is_hfs_excluded(c))
+}
+
 #[inline]
 #[allow(unsafe_code)]
 fn decompose<D, F>(c: char, decompose_char: D, mut emit_char: F)
@@ -0,0 +1,452 @@
+// Copyright 2012-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The PRECIS framework of [RFC 8264](https://www.rfc-editor.org/rfc/rfc8264)
+//! and its profiles for usernames and passwords
+//! ([RFC 8265](https://www.rfc-editor.org/rfc/rfc8265)) and nicknames
+//! ([RFC 8266](https://www.rfc-editor.org/rfc/rfc8266)).
+//!
+//! PRECIS assigns every code point a [`DerivedProperty`] from its Unicode
+//! properties, which decides whether it is allowed in the two string classes:
+//! the IdentifierClass of usernames, which is limited to letters and digits,
+//! and the FreeformClass of passwords and nicknames, which also allows
+//! spaces, symbols and punctuation. A [`Profile`] maps a string (width
+//! mapping, space mapping, case mapping and normalization to NFC or NFKC)
+//! before checking it against its string class.
+//!
+//! Strings are compared by enforcing the profile on both of them and
+//! comparing the results, so for example usernames must be stored in their
+//! enforced form.
+//!
+//! ```rust
+//! use unicode_normalization::precis::{DerivedProperty, Error, Profile};
+//!
+//! let username = Profile::UsernameCaseMapped;
+//! assert_eq!(username.enforce("Juliet").unwrap(), "juliet");
+//! assert_eq!(username.enforce("\u{ff2a}uliet").unwrap(), "juliet");
+//! assert_eq!(
+//!     username.enforce("juliet\u{2661}"),
+//!     Err(Error::Disallowed('\u{2661}', DerivedProperty::FreePVal))
+//! );
+//!
+//! let nickname = Profile::Nickname;
+//! assert_eq!(nickname.enforce("  Richard  \u{2163} ").unwrap(), "Richard IV");
+//! assert!(nickname.compare("Foo Bar", "foo bar").unwrap());
+//! ```
+//!
+//! The width mapping, the case mapping, the derived property and the
+//! normalization all use the tables of this crate, so that they follow the
+//! same version of Unicode.
+
+use crate::idna::{check_bidi, is_rtl, joiner_allowed};
+use crate::in_place::{normalize_nfc_in_place, normalize_nfkc_in_place};
+use crate::lookups::{
+    is_case_ignorable, is_cased, is_greek, is_hebrew, is_hiragana_katakana_or_han, lowercase,
+    precis_property, width_mapping,
+};
+pub use crate::properties::DerivedProperty;
+use crate::tables::{is_space_separator, lowercase_final_sigma, lowercase_special};
+use core::fmt;
+
+#[cfg(not(feature = "std"))]
+use alloc::string::String;
+
+/// Look up the PRECIS derived property of a character.
+#[inline]
+pub fn derived_property(c: char) -> DerivedProperty {
+    if c.is_ascii() {
+        match c {
+            '!'..='~' => DerivedProperty::PValid,
+            ' ' => DerivedProperty::FreePVal,
+            _ => DerivedProperty::Disallowed,
+        }
+    } else {
+        precis_property(c)
+    }
+}
+
+/// The PRECIS profiles of RFC 8265 and RFC 8266.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Profile {
+    /// The UsernameCaseMapped profile of RFC 8265 section 3.3: maps
+    /// fullwidth and halfwidth characters to their usual forms, lowercases
+    /// and normalizes to NFC, then checks the string against the
+    /// IdentifierClass and the Bidi Rule.
+    ///
+    /// Usernames made of several parts separated by spaces must be split by
+    /// the application, and each part enforced separately.
+    UsernameCaseMapped,
+    /// The UsernameCasePreserved profile of RFC 8265 section 3.4, which is
+    /// UsernameCaseMapped without the case mapping.
+    UsernameCasePreserved,
+    /// The OpaqueString profile of RFC 8265 section 4.2, for passwords: maps
+    /// the non-ASCII spaces to U+0020 SPACE and normalizes to NFC, then checks
+    /// the string against the FreeformClass.
+    OpaqueString,
+    /// The Nickname profile of RFC 8266: maps the non-ASCII spaces to U+0020
+    /// SPACE, removes leading and trailing spaces, collapses consecutive
+    /// spaces and normalizes to NFKC, then checks the string against the
+    /// FreeformClass. Nicknames are also lowercased for comparison.
+    Nickname,
+}
+
+impl Profile {
+    /// Enforce the profile on a string, returning it in the form which
+    /// should be stored or displayed.
+    pub fn enforce(self, s: &str) -> Result<String, Error> {
+        self.apply(s, self == Profile::UsernameCaseMapped)
+    }
+
+    /// Compare two strings according to the profile, returning an error if
+    /// either of them can't be enforced.
+    pub fn compare(self, a: &str, b: &str) -> Result<bool, Error> {
+        let case_mapped = match self {
+            Profile::UsernameCaseMapped | Profile::Nickname => true,
+            Profile::UsernameCasePreserved | Profile::OpaqueString => false,
+        };
+        Ok(self.apply(a, case_mapped)? == self.apply(b, case_mapped)?)
+    }
+
+    /// Apply the rules of the profile until the string is stable, then check
+    /// it. RFC 8264 section 7 gives up after three additional passes, which
+    /// are only needed for contrived strings.
+    fn apply(self, s: &str, case_mapped: bool) -> Result<String, Error> {
+        let mut prepared = self.map(s, case_mapped);
+        let mut passes = 0;
+        loop {
+            let again = self.map(&prepared, case_mapped);
+            if again == prepared {
+                break;
+            }
+            passes += 1;
+            if passes == 3 {
+                return Err(Error::Unstable);
+            }
+            prepared = again;
+        }
+
+        self.check(&prepared)?;
+        Ok(prepared)
+    }
+
+    /// Apply the width mapping, additional mapping, case mapping and
+    /// normalization rules of the profile, in that order.
+    fn map(self, s: &str, case_mapped: bool) -> String {
+        let mut out = String::with_capacity(s.len());
+        match self {
+            Profile::UsernameCaseMapped | Profile::UsernameCasePreserved => {
+                out.extend(s.chars().map(|c| width_mapping(c).unwrap_or(c)));
+                map_case(&mut out, case_mapped);
+                normalize_nfc_in_place(&mut out);
+            }
+            Profile::OpaqueString => {
+                out.extend(
+                    s.chars()
+                        .map(|c| if is_space_separator(c) { ' ' } else { c }),
+                );
+                map_case(&mut out, case_mapped);
+                normalize_nfc_in_place(&mut out);
+            }
+            Profile::Nickname => {
+                for c in s.chars() {
+                    if is_space_separator(c) {
+                        if !out.is_empty() && !out.ends_with(' ') {
+                            out.push(' ');
+                        }
+                    } else {
+                        out.push(c);
+                    }
+                }
+                if out.ends_with(' ') {
+                    out.pop();
+                }
+                map_case(&mut out, case_mapped);
+                normalize_nfkc_in_place(&mut out);
+            }
+        }
+        out
+    }
+
+    /// Check a mapped string against the string class and the directionality
+    /// rule of the profile.
+    fn check(self, s: &str) -> Result<(), Error> {
+        if s.is_empty() {
+            return Err(Error::Empty);
+        }
+
+        let identifier = match self {
+            Profile::UsernameCaseMapped | Profile::UsernameCasePreserved => true,
+            Profile::OpaqueString | Profile::Nickname => false,
+        };
+        for (i, c) in s.char_indices() {
+            match derived_property(c) {
+                DerivedProperty::PValid => {}
+                DerivedProperty::FreePVal if !identifier => {}
+                DerivedProperty::ContextJ if joiner_allowed(s, i, c) => {}
+                DerivedProperty::ContextO if contexto_allowed(s, i, c) => {}
+                DerivedProperty::ContextJ | DerivedProperty::ContextO => {
+                    return Err(Error::Context(c))
+                }
+                property => return Err(Error::Disallowed(c, property)),
+            }
+        }
+
+        if identifier && s.chars().any(is_rtl) && !check_bidi(s) {
+            return Err(Error::Bidi);
+        }
+        Ok(())
+    }
+}
+
+/// Apply the case mapping rule: the full lowercase mapping of Unicode's
+/// `toLowercase()`, including the `Final_Sigma` condition but not the
+/// language-specific ones.
+fn map_case(s: &mut String, case_mapped: bool) {
+    if !case_mapped {
+        return;
+    }
+    let mut lower = String::with_capacity(s.len());
+    for (i, c) in s.char_indices() {
+        match lowercase_final_sigma(c) {
+            Some(l) if is_final_sigma(s, i, c) => lower.push(l),
+            _ => match lowercase_special(c) {
+                Some(l) => lower.extend(l),
+                None => lower.push(lowercase(c).unwrap_or(c)),
+            },
+        }
+    }
+    *s = lower;
+}
+
+/// Return whether the character `c` at byte offset `i` in `s` ends a word, as
+/// the `Final_Sigma` condition requires: it follows a cased character and
+/// isn't followed by one, ignoring the case-ignorable characters between.
+fn is_final_sigma(s: &str, i: usize, c: char) -> bool {
+    let mut before = s[..i].chars().rev().filter(|&d| !is_case_ignorable(d));
+    let mut after = s[i + c.len_utf8()..]
+        .chars()
+        .filter(|&d| !is_case_ignorable(d));
+    before.next().map_or(false, is_cased) && !after.next().map_or(false, is_cased)
+}
+
+/// Return whether the character `c` at byte offset `i` in `s` satisfies its
+/// CONTEXTO rule from RFC 5892 appendix A.
+fn contexto_allowed(s: &str, i: usize, c: char) -> bool {
+    let (before, after) = (&s[..i], &s[i + c.len_utf8()..]);
+    match c {
+        // MIDDLE DOT, between two l as in Catalan.
+        '\u{b7}' => before.ends_with('l') && after.starts_with('l'),
+        // GREEK LOWER NUMERAL SIGN (KERAIA), before a Greek character.
+        '\u{375}' => after.chars().next().map_or(false, is_greek),
+        // HEBREW PUNCTUATION GERESH and GERSHAYIM, after a Hebrew character.
+        '\u{5f3}' | '\u{5f4}' => before.chars().next_back().map_or(false, is_hebrew),
+        // KATAKANA MIDDLE DOT, in a string with Japanese characters.
+        '\u{30fb}' => s.chars().any(is_hiragana_katakana_or_han),
+        // The ARABIC-INDIC DIGITS and EXTENDED ARABIC-INDIC DIGITS can't be
+        // mixed.
+        '\u{660}'..='\u{669}' => !s.chars().any(|c| ('\u{6f0}'..='\u{6f9}').contains(&c)),
+        '\u{6f0}'..='\u{6f9}' => !s.chars().any(|c| ('\u{660}'..='\u{669}').contains(&c)),
+        _ => false,
+    }
+}
+
+/// An error returned when a string can't be enforced by a PRECIS profile.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Error {
+    /// The string is empty, after the mappings of the profile.
+    Empty,
+    /// The string contains a code point which the string class of the
+    /// profile doesn't allow, with its derived property.
+    Disallowed(char, DerivedProperty),
+    /// The string contains a `CONTEXTJ` or `CONTEXTO` code point outside of
+    /// the context its rule requires.
+    Context(char),
+    /// The string contains right-to-left characters and breaks the Bidi Rule
+    /// of RFC 5893.
+    Bidi,
+    /// Applying the rules of the profile repeatedly doesn't result in a
+    /// stable string.
+    Unstable,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Empty => f.write_str("empty string"),
+            Error::Disallowed(c, _) => write!(f, "disallowed code point {:?}", c),
+            Error::Context(c) => write!(f, "code point {:?} not allowed in this context", c),
+            Error::Bidi => f.write_str("string breaks the Bidi Rule"),
+            Error::Unstable => f.write_str("string is not stable under the profile"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+#[cfg(test)]
+mod tests {
+    use super::{derived_property, map_case, DerivedProperty, Error, Profile};
+    #[cfg(not(feature = "std"))]
+    use alloc::string::String;
+
+    #[test]
+    fn test_derived_property() {
+        assert_eq!(derived_property('a'), DerivedProperty::PValid);
+        assert_eq!(derived_property('@'), DerivedProperty::PValid);
+        assert_eq!(derived_property(' '), DerivedProperty::FreePVal);
+        assert_eq!(derived_property('\t'), DerivedProperty::Disallowed);
+        assert_eq!(derived_property('\u{df}'), DerivedProperty::PValid);
+        assert_eq!(derived_property('\u{3a3}'), DerivedProperty::PValid);
+        assert_eq!(derived_property('\u{301}'), DerivedProperty::PValid);
+        assert_eq!(derived_property('\u{2163}'), DerivedProperty::FreePVal);
+        assert_eq!(derived_property('\u{ff21}'), DerivedProperty::FreePVal);
+        assert_eq!(derived_property('\u{2665}'), DerivedProperty::FreePVal);
+        assert_eq!(derived_property('\u{200d}'), DerivedProperty::ContextJ);
+        assert_eq!(derived_property('\u{b7}'), DerivedProperty::ContextO);
+        assert_eq!(derived_property('\u{ad}'), DerivedProperty::Disallowed);
+        assert_eq!(derived_property('\u{1100}'), DerivedProperty::Disallowed);
+        assert_eq!(derived_property('\u{640}'), DerivedProperty::Disallowed);
+        assert_eq!(derived_property('\u{fffe}'), DerivedProperty::Disallowed);
+        assert_eq!(derived_property('\u{e000}'), DerivedProperty::Disallowed);
+        assert_eq!(derived_property('\u{378}'), DerivedProperty::Unassigned);
+    }
+
+    #[test]
+    fn test_username() {
+        // Examples from RFC 8265 section 3.5.
+        let mapped = Profile::UsernameCaseMapped;
+        assert_eq!(
+            mapped.enforce("juliet@example.com").unwrap(),
+            "juliet@example.com"
+        );
+        assert_eq!(mapped.enforce("fu\u{df}ball").unwrap(), "fu\u{df}ball");
+        assert_eq!(mapped.enforce("\u{3c0}").unwrap(), "\u{3c0}");
+        assert_eq!(mapped.enforce("\u{3a3}").unwrap(), "\u{3c3}");
+        assert_eq!(mapped.enforce("\u{3c2}").unwrap(), "\u{3c2}");
+        // Final_Sigma applies to the word as a whole.
+        assert_eq!(
+            mapped.enforce("\u{39f}\u{394}\u{39f}\u{3a3}").unwrap(),
+            "\u{3bf}\u{3b4}\u{3bf}\u{3c2}"
+        );
+        assert_eq!(mapped.enforce("A\u{30a}").unwrap(), "\u{e5}");
+        assert_eq!(mapped.enforce(""), Err(Error::Empty));
+        assert_eq!(
+            mapped.enforce("foo bar"),
+            Err(Error::Disallowed(' ', DerivedProperty::FreePVal))
+        );
+        assert_eq!(
+            mapped.enforce("\u{265a}"),
+            Err(Error::Disallowed('\u{265a}', DerivedProperty::FreePVal))
+        );
+        assert_eq!(
+            mapped.enforce("henry\u{2163}"),
+            Err(Error::Disallowed('\u{2173}', DerivedProperty::FreePVal))
+        );
+
+        let preserved = Profile::UsernameCasePreserved;
+        assert_eq!(preserved.enforce("Juliet").unwrap(), "Juliet");
+        assert_eq!(preserved.enforce("\u{ff2a}uliet").unwrap(), "Juliet");
+        assert!(!preserved.compare("Juliet", "juliet").unwrap());
+        assert!(mapped.compare("Juliet", "juliet").unwrap());
+        assert!(mapped.compare("juliet", "").is_err());
+    }
+
+    #[test]
+    fn test_case_mapping() {
+        fn mapped(s: &str) -> String {
+            let mut s = String::from(s);
+            map_case(&mut s, true);
+            s
+        }
+        assert_eq!(mapped("Juliet"), "juliet");
+        assert_eq!(mapped("\u{3a3}\u{391}"), "\u{3c3}\u{3b1}");
+        assert_eq!(mapped("\u{391}\u{3a3}."), "\u{3b1}\u{3c2}.");
+        assert_eq!(mapped("\u{391}\u{3a3}'\u{392}"), "\u{3b1}\u{3c3}'\u{3b2}");
+        assert_eq!(mapped("\u{130}"), "i\u{307}");
+        // Added in Unicode 16.0.
+        assert_eq!(mapped("\u{a7cb}"), "\u{264}");
+    }
+
+    #[test]
+    fn test_username_rules() {
+        let mapped = Profile::UsernameCaseMapped;
+        assert!(mapped.enforce("\u{5e9}\u{5dc}\u{5d5}\u{5dd}").is_ok());
+        assert_eq!(mapped.enforce("\u{5e9}a"), Err(Error::Bidi));
+        assert!(mapped.enforce("col\u{b7}legi").is_ok());
+        assert_eq!(
+            mapped.enforce("co\u{b7}legi"),
+            Err(Error::Context('\u{b7}'))
+        );
+        assert!(mapped.enforce("\u{30a2}\u{30fb}\u{30a4}").is_ok());
+        assert_eq!(
+            mapped.enforce("a\u{30fb}b"),
+            Err(Error::Context('\u{30fb}'))
+        );
+        assert!(mapped.enforce("\u{628}\u{200c}\u{627}").is_ok());
+        assert_eq!(
+            mapped.enforce("a\u{200d}b"),
+            Err(Error::Context('\u{200d}'))
+        );
+        assert_eq!(
+            mapped.enforce("\u{661}\u{6f1}"),
+            Err(Error::Context('\u{661}'))
+        );
+    }
+
+    #[test]
+    fn test_opaque_string() {
+        // Examples from RFC 8265 section 4.3.
+        let opaque = Profile::OpaqueString;
+        assert_eq!(
+            opaque.enforce("correct horse battery staple").unwrap(),
+            "correct horse battery staple"
+        );
+        assert_eq!(
+            opaque.enforce("\u{3c0}\u{df}\u{e5}").unwrap(),
+            "\u{3c0}\u{df}\u{e5}"
+        );
+        assert_eq!(
+            opaque.enforce("Jack of \u{2666}s").unwrap(),
+            "Jack of \u{2666}s"
+        );
+        assert_eq!(opaque.enforce("foo\u{1680}bar").unwrap(), "foo bar");
+        assert_eq!(opaque.enforce(""), Err(Error::Empty));
+        assert_eq!(
+            opaque.enforce("my cat is a \u{9}by"),
+            Err(Error::Disallowed('\u{9}', DerivedProperty::Disallowed))
+        );
+        assert!(!opaque
+            .compare(
+                "Correct Horse Battery Staple",
+                "correct horse battery staple"
+            )
+            .unwrap());
+    }
+
+    #[test]
+    fn test_nickname() {
+        // Examples from RFC 8266 section 4.
+        let nickname = Profile::Nickname;
+        assert_eq!(nickname.enforce("Foo").unwrap(), "Foo");
+        assert_eq!(nickname.enforce("  Foo  Bar ").unwrap(), "Foo Bar");
+        assert_eq!(nickname.enforce("\u{3a3}").unwrap(), "\u{3a3}");
+        assert_eq!(nickname.enforce("\u{221e}").unwrap(), "\u{221e}");
+        assert_eq!(nickname.enforce("Richard \u{2163}").unwrap(), "Richard IV");
+        assert_eq!(nickname.enforce("\u{210c}").unwrap(), "H");
+        assert_eq!(nickname.enforce(" \u{3000} "), Err(Error::Empty));
+
+        assert!(nickname.compare("Foo", "foo").unwrap());
+        assert!(nickname.compare("Foo Bar", " foo  bar").unwrap());
+        assert!(nickname.compare("\u{3a3}", "\u{3c3}").unwrap());
+        assert!(nickname.compare("\u{210c}", "h").unwrap());
+        assert!(!nickname.compare("\u{3c3}", "\u{3c2}").unwrap());
+    }
+}
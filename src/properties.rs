@@ -0,0 +1,74 @@
+// Copyright 2012-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The property values which the generated tables refer to, re-exported by
+//! the modules which use them.
+
+/// The `Bidi_Class` property values, see
+/// [UAX #44](https://www.unicode.org/reports/tr44/#Bidi_Class_Values).
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum BidiClass {
+    AL,
+    AN,
+    B,
+    BN,
+    CS,
+    EN,
+    ES,
+    ET,
+    FSI,
+    L,
+    LRE,
+    LRI,
+    LRO,
+    NSM,
+    ON,
+    PDF,
+    PDI,
+    R,
+    RLE,
+    RLI,
+    RLO,
+    S,
+    WS,
+}
+
+/// The `Joining_Type` property values used by the CONTEXTJ rules.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum JoiningType {
+    Dual,
+    Left,
+    Right,
+    Transparent,
+}
+
+/// The derived property of a code point in the PRECIS framework
+/// (RFC 8264 section 8).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DerivedProperty {
+    /// `PVALID`: allowed in both string classes, such as letters and digits.
+    PValid,
+    /// `ID_DIS` or `FREE_PVAL`: allowed in the FreeformClass only, such as
+    /// spaces, symbols, punctuation and characters with compatibility
+    /// decompositions.
+    FreePVal,
+    /// `CONTEXTJ`: the joiners U+200C and U+200D, allowed in some contexts
+    /// by the rules of RFC 5892 appendix A.
+    ContextJ,
+    /// `CONTEXTO`: other characters allowed in some contexts by the rules of
+    /// RFC 5892 appendix A, such as U+00B7 MIDDLE DOT in Catalan.
+    ContextO,
+    /// `DISALLOWED`: allowed in neither string class, such as controls and
+    /// default ignorable code points.
+    Disallowed,
+    /// `UNASSIGNED`: not assigned in the supported version of Unicode.
+    Unassigned,
+}
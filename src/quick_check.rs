@@ -1,7 +1,9 @@
-use crate::lookups::canonical_combining_class;
+use crate::form::Form;
+use crate::lookups::{canonical_combining_class, nfkc_casefold_mapped};
 use crate::stream_safe;
 use crate::tables;
 use crate::UnicodeNormalization;
+use core::mem;
 
 /// QuickCheck quickly determines if a string is normalized, it can return
 /// `Maybe`
@@ -21,7 +23,7 @@ pub enum IsNormalized {
 
 // https://unicode.org/reports/tr15/#Detecting_Normalization_Forms
 #[inline]
-fn quick_check<F, I>(s: I, is_allowed: F, stream_safe: bool) -> IsNormalized
+pub(crate) fn quick_check<F, I>(s: I, is_allowed: F, stream_safe: bool) -> IsNormalized
 where
     I: Iterator<Item = char>,
     F: Fn(char) -> IsNormalized,
@@ -68,6 +70,85 @@ where
     result
 }
 
+/// A word with the high bit of each byte set, to test several bytes for
+/// ASCII at once.
+const NON_ASCII_MASK: usize = !0 / 0xff * 0x80;
+
+const WORD_SIZE: usize = mem::size_of::<usize>();
+
+/// Skip whole words of ASCII starting at `i`, returning the start of the first
+/// word which contains a non-ASCII byte or runs past the end of `bytes`.
+#[inline]
+fn skip_ascii_words(bytes: &[u8], mut i: usize) -> usize {
+    while let Some(word) = bytes.get(i..i + WORD_SIZE) {
+        let mut buf = [0; WORD_SIZE];
+        buf.copy_from_slice(word);
+        if usize::from_ne_bytes(buf) & NON_ASCII_MASK != 0 {
+            break;
+        }
+        i += WORD_SIZE;
+    }
+    i
+}
+
+/// Find the longest prefix of `s` that the quick check proves normalized, and
+/// return the byte offset of the last normalization boundary within it.
+///
+/// A boundary is placed before every character which is a starter and whose
+/// quick check property is `Yes`: such a character never reorders or composes
+/// with anything that precedes it, so `s[..offset]` is normalized and
+/// `s[offset..]` can be normalized independently of it. If the whole string
+/// passes, `s.len()` is returned.
+#[inline]
+pub(crate) fn quick_check_span(s: &str, form: Form) -> usize {
+    let bytes = s.as_bytes();
+    let mut last_cc = 0u8;
+    let mut boundary = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        // ASCII is always allowed and a starter, so it always begins a new
+        // segment. Test it on the bytes without decoding a `char`, skipping
+        // whole words of it at a time.
+        if bytes[i] <= 0x7f {
+            let next = skip_ascii_words(bytes, i + 1);
+            last_cc = 0;
+            boundary = next - 1;
+            i = next;
+            continue;
+        }
+
+        // Everything below U+0300 is a starter which is allowed in NFC. All
+        // of U+0080..U+02FF is encoded as two bytes with a lead byte below
+        // 0xCC, so skip it without decoding either.
+        if form == Form::Nfc && bytes[i] < 0xcc {
+            last_cc = 0;
+            boundary = i;
+            i += 2;
+            continue;
+        }
+
+        let ch = match s[i..].chars().next() {
+            Some(ch) => ch,
+            None => break,
+        };
+        let cc = canonical_combining_class(ch);
+        if last_cc > cc && cc != 0 {
+            return boundary;
+        }
+        match form.quick_check(ch) {
+            IsNormalized::Yes => {
+                if cc == 0 {
+                    boundary = i;
+                }
+            }
+            IsNormalized::No | IsNormalized::Maybe => return boundary,
+        }
+        last_cc = cc;
+        i += ch.len_utf8();
+    }
+    s.len()
+}
+
 /// Quickly check if a string is in NFC, potentially returning
 /// `IsNormalized::Maybe` if further checks are necessary.  In this case a check
 /// like `s.chars().nfc().eq(s.chars())` should suffice.
@@ -94,6 +175,35 @@ pub fn is_nfkd_quick<I: Iterator<Item = char>>(s: I) -> IsNormalized {
     quick_check(s, tables::qc_nfkd, false)
 }
 
+/// Quickly check if a string is closed under `NFKC_Casefold`.
+#[inline]
+pub fn is_nfkc_casefold_quick<I: Iterator<Item = char>>(s: I) -> IsNormalized {
+    // ASCII upper case letters are mapped, but `quick_check` always allows
+    // ASCII, so look out for them on the way.
+    let mut has_ascii_upper = false;
+    let result = quick_check(
+        s.inspect(|c| has_ascii_upper |= c.is_ascii_uppercase()),
+        qc_nfkc_casefold,
+        false,
+    );
+    if has_ascii_upper {
+        IsNormalized::No
+    } else {
+        result
+    }
+}
+
+/// Every character that has an `NFKC_Casefold` mapping is
+/// `Changes_When_NFKC_Casefolded`, and the mapping is followed by NFC.
+#[inline]
+fn qc_nfkc_casefold(c: char) -> IsNormalized {
+    if nfkc_casefold_mapped(c).is_some() {
+        IsNormalized::No
+    } else {
+        tables::qc_nfc(c)
+    }
+}
+
 /// Quickly check if a string is Stream-Safe NFC.
 #[inline]
 pub fn is_nfc_stream_safe_quick<I: Iterator<Item = char>>(s: I) -> IsNormalized {
@@ -106,9 +216,67 @@ pub fn is_nfd_stream_safe_quick<I: Iterator<Item = char>>(s: I) -> IsNormalized
     quick_check(s, tables::qc_nfd, true)
 }
 
+/// Return the byte offset up to which `s` is proven to be in NFC by the quick
+/// check, like ICU's `spanQuickCheckYes`.
+///
+/// The offset is that of the last normalization boundary before the first
+/// character whose NFC quick check property is `No` or `Maybe`, or which is
+/// out of canonical order, or `s.len()` if there is no such character. The
+/// prefix `s[..offset]` is in NFC, and the remainder can be normalized
+/// independently of it:
+///
+/// ```rust
+/// use unicode_normalization::{nfc_quick_span, UnicodeNormalization};
+///
+/// let s = "Introduccio\u{301}n a Unicode.pdf";
+/// let offset = nfc_quick_span(s);
+/// assert_eq!(&s[..offset], "Introducci");
+///
+/// let mut nfc = s[..offset].to_string();
+/// nfc.extend(s[offset..].nfc());
+/// assert_eq!(nfc, "Introducci\u{f3}n a Unicode.pdf");
+/// ```
+///
+/// As `Maybe` characters may turn out to be normalized in context, the
+/// remainder is not necessarily unnormalized.
+#[inline]
+pub fn nfc_quick_span(s: &str) -> usize {
+    quick_check_span(s, Form::Nfc)
+}
+
+/// Return the byte offset up to which `s` is proven to be in NFKC by the quick
+/// check.
+///
+/// See [`nfc_quick_span`] for details.
+#[inline]
+pub fn nfkc_quick_span(s: &str) -> usize {
+    quick_check_span(s, Form::Nfkc)
+}
+
+/// Return the byte offset up to which `s` is proven to be in NFD by the quick
+/// check.
+///
+/// See [`nfc_quick_span`] for details.
+#[inline]
+pub fn nfd_quick_span(s: &str) -> usize {
+    quick_check_span(s, Form::Nfd)
+}
+
+/// Return the byte offset up to which `s` is proven to be in NFKD by the quick
+/// check.
+///
+/// See [`nfc_quick_span`] for details.
+#[inline]
+pub fn nfkd_quick_span(s: &str) -> usize {
+    quick_check_span(s, Form::Nfkd)
+}
+
 /// Authoritatively check if a string is in NFC.
 #[inline]
 pub fn is_nfc(s: &str) -> bool {
+    // The proven prefix can be skipped, as the rest of the string is
+    // normalized independently of it.
+    let s = &s[nfc_quick_span(s)..];
     match is_nfc_quick(s.chars()) {
         IsNormalized::Yes => true,
         IsNormalized::No => false,
@@ -119,6 +287,9 @@ pub fn is_nfc(s: &str) -> bool {
 /// Authoritatively check if a string is in NFKC.
 #[inline]
 pub fn is_nfkc(s: &str) -> bool {
+    // The proven prefix can be skipped, as the rest of the string is
+    // normalized independently of it.
+    let s = &s[nfkc_quick_span(s)..];
     match is_nfkc_quick(s.chars()) {
         IsNormalized::Yes => true,
         IsNormalized::No => false,
@@ -129,6 +300,9 @@ pub fn is_nfkc(s: &str) -> bool {
 /// Authoritatively check if a string is in NFD.
 #[inline]
 pub fn is_nfd(s: &str) -> bool {
+    // The proven prefix can be skipped, as the rest of the string is
+    // normalized independently of it.
+    let s = &s[nfd_quick_span(s)..];
     match is_nfd_quick(s.chars()) {
         IsNormalized::Yes => true,
         IsNormalized::No => false,
@@ -139,6 +313,9 @@ pub fn is_nfd(s: &str) -> bool {
 /// Authoritatively check if a string is in NFKD.
 #[inline]
 pub fn is_nfkd(s: &str) -> bool {
+    // The proven prefix can be skipped, as the rest of the string is
+    // normalized independently of it.
+    let s = &s[nfkd_quick_span(s)..];
     match is_nfkd_quick(s.chars()) {
         IsNormalized::Yes => true,
         IsNormalized::No => false,
@@ -146,6 +323,17 @@ pub fn is_nfkd(s: &str) -> bool {
     }
 }
 
+/// Authoritatively check if a string is closed under `NFKC_Casefold`, i.e.
+/// whether `s.nfkc_casefold()` would return it unchanged.
+#[inline]
+pub fn is_nfkc_casefold(s: &str) -> bool {
+    match is_nfkc_casefold_quick(s.chars()) {
+        IsNormalized::Yes => true,
+        IsNormalized::No => false,
+        IsNormalized::Maybe => s.chars().eq(s.chars().nfkc_casefold()),
+    }
+}
+
 /// Authoritatively check if a string is Stream-Safe NFC.
 #[inline]
 pub fn is_nfc_stream_safe(s: &str) -> bool {
@@ -168,7 +356,13 @@ pub fn is_nfd_stream_safe(s: &str) -> bool {
 
 #[cfg(test)]
 mod tests {
-    use super::{is_nfc_stream_safe_quick, is_nfd_stream_safe_quick, IsNormalized};
+    use super::{
+        is_nfc_stream_safe_quick, is_nfd_stream_safe_quick, nfc_quick_span, nfd_quick_span,
+        nfkd_quick_span, IsNormalized,
+    };
+
+    #[cfg(not(feature = "std"))]
+    use alloc::string::String;
 
     #[test]
     fn test_stream_safe_nfd() {
@@ -187,4 +381,37 @@ mod tests {
         let too_much = "not ok\u{e0}\u{031b}\u{0316}\u{0317}\u{0318}\u{0319}\u{031c}\u{031d}\u{031e}\u{0301}\u{0302}\u{0303}\u{0304}\u{0305}\u{0306}\u{0307}\u{0308}\u{0309}\u{030a}\u{030b}\u{030c}\u{030d}\u{030e}\u{030f}\u{0310}\u{0311}\u{0312}\u{0313}\u{0314}\u{0315}\u{031a}y";
         assert_eq!(is_nfc_stream_safe_quick(too_much.chars()), IsNormalized::No);
     }
+
+    #[test]
+    fn test_quick_span() {
+        assert_eq!(nfc_quick_span(""), 0);
+        assert_eq!(nfc_quick_span("abc \u{e0}"), 6);
+        // The span ends before the starter that U+0301 may compose with.
+        assert_eq!(nfc_quick_span("ab\u{301}"), 1);
+        assert_eq!(nfd_quick_span("ab\u{301}"), 4);
+        // Canonical order violations end the span, too.
+        assert_eq!(nfd_quick_span("xa\u{315}\u{300}b"), 1);
+        // The span is conservative and always ends before the last starter.
+        assert_eq!(nfkd_quick_span("ab\u{2026}"), 1);
+    }
+
+    #[test]
+    fn test_quick_span_fast_paths() {
+        // Non-starters and characters that aren't `Yes` at every offset
+        // relative to a run of ASCII, to cover the word-at-a-time skip.
+        let ascii = "all types of normalized text";
+        for i in 0..ascii.len() {
+            for &(c, width) in &[("\u{301}", 1), ("\u{2126}", 1), ("\u{e9}\u{301}", 0)] {
+                let s = String::from(&ascii[..i]) + c + &ascii[i..];
+                assert_eq!(nfc_quick_span(&s), i.saturating_sub(width));
+            }
+        }
+
+        // Everything below U+0300 is skipped in NFC, but not in NFD.
+        let latin = "\u{e9}t\u{e9} \u{17e}\u{2c6}\u{2ff}";
+        assert_eq!(nfc_quick_span(latin), latin.len());
+        assert_eq!(nfd_quick_span(latin), 0);
+        let s = String::from(latin) + "\u{301}";
+        assert_eq!(nfc_quick_span(&s), latin.len() - 2);
+    }
 }
@@ -9,12 +9,38 @@
 // except according to those terms.
 
 use crate::decompose::Decompositions;
+use crate::lookups::canonical_combining_class;
+use crate::normalize::{compose, compose_hfs, hfs_combining_class};
 use core::{
     fmt::{self, Write},
     iter::FusedIterator,
 };
 use tinyvec::TinyVec;
 
+#[derive(Clone, Copy)]
+enum CompositionType {
+    Standard,
+    Hfs,
+}
+
+impl CompositionType {
+    #[inline]
+    fn combining_class(self, c: char) -> u8 {
+        match self {
+            CompositionType::Standard => canonical_combining_class(c),
+            CompositionType::Hfs => hfs_combining_class(c),
+        }
+    }
+
+    #[inline]
+    fn compose(self, a: char, b: char) -> Option<char> {
+        match self {
+            CompositionType::Standard => compose(a, b),
+            CompositionType::Hfs => compose_hfs(a, b),
+        }
+    }
+}
+
 #[derive(Clone)]
 enum RecompositionState {
     Composing,
@@ -25,6 +51,7 @@ enum RecompositionState {
 /// External iterator for a string recomposition's characters.
 #[derive(Clone)]
 pub struct Recompositions<I> {
+    kind: CompositionType,
     iter: Decompositions<I>,
     state: RecompositionState,
     buffer: TinyVec<[char; 4]>,
@@ -40,6 +67,7 @@ impl<I: Iterator<Item = char>> Recompositions<I> {
     #[inline]
     pub fn new_canonical(iter: I) -> Self {
         Recompositions {
+            kind: self::CompositionType::Standard,
             iter: Decompositions::new_canonical(iter),
             state: self::RecompositionState::Composing,
             buffer: TinyVec::new(),
@@ -55,6 +83,7 @@ impl<I: Iterator<Item = char>> Recompositions<I> {
     #[inline]
     pub fn new_compatible(iter: I) -> Self {
         Recompositions {
+            kind: self::CompositionType::Standard,
             iter: Decompositions::new_compatible(iter),
             state: self::RecompositionState::Composing,
             buffer: TinyVec::new(),
@@ -62,6 +91,41 @@ impl<I: Iterator<Item = char>> Recompositions<I> {
             last_ccc: None,
         }
     }
+
+    /// Create a new recomposition iterator for the `NFKC_Casefold` mapping
+    /// (NFKC_CF)
+    ///
+    /// Note that this iterator can also be obtained by directly calling [`.nfkc_casefold()`](crate::UnicodeNormalization::nfkc_casefold)
+    /// on the iterator.
+    #[inline]
+    pub fn new_nfkc_casefold(iter: I) -> Self {
+        Recompositions {
+            kind: self::CompositionType::Standard,
+            iter: Decompositions::new_nfkc_casefold(iter),
+            state: self::RecompositionState::Composing,
+            buffer: TinyVec::new(),
+            composee: None,
+            last_ccc: None,
+        }
+    }
+
+    /// Create a new recomposition iterator for the variant of NFC used by the
+    /// Finder on HFS+ volumes, the inverse of the HFS+ variant of NFD: it
+    /// never composes into a character which HFS+ doesn't decompose.
+    ///
+    /// Note that this iterator can also be obtained by directly calling [`.hfs_nfc()`](crate::UnicodeNormalization::hfs_nfc)
+    /// on the iterator.
+    #[inline]
+    pub fn new_hfs(iter: I) -> Self {
+        Recompositions {
+            kind: self::CompositionType::Hfs,
+            iter: Decompositions::new_hfs(iter),
+            state: self::RecompositionState::Composing,
+            buffer: TinyVec::new(),
+            composee: None,
+            last_ccc: None,
+        }
+    }
 }
 
 impl<I: Iterator<Item = char>> Iterator for Recompositions<I> {
@@ -74,8 +138,9 @@ impl<I: Iterator<Item = char>> Iterator for Recompositions<I> {
         loop {
             match self.state {
                 Composing => {
+                    let kind = self.kind;
                     for ch in self.iter.by_ref() {
-                        let ch_class = super::char::canonical_combining_class(ch);
+                        let ch_class = kind.combining_class(ch);
                         let k = match self.composee {
                             None => {
                                 if ch_class != 0 {
@@ -87,7 +152,7 @@ impl<I: Iterator<Item = char>> Iterator for Recompositions<I> {
                             Some(k) => k,
                         };
                         match self.last_ccc {
-                            None => match super::char::compose(k, ch) {
+                            None => match kind.compose(k, ch) {
                                 Some(r) => {
                                     self.composee = Some(r);
                                     continue;
@@ -114,7 +179,7 @@ impl<I: Iterator<Item = char>> Iterator for Recompositions<I> {
                                     self.last_ccc = Some(ch_class);
                                     continue;
                                 }
-                                match super::char::compose(k, ch) {
+                                match kind.compose(k, ch) {
                                     Some(r) => {
                                         self.composee = Some(r);
                                         continue;
@@ -0,0 +1,381 @@
+// Copyright 2012-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Folding of strings for search, in the spirit of the withdrawn
+//! [UTR #30](https://www.unicode.org/reports/tr30/).
+
+use crate::casefold::{CaseFolding, Folded};
+use crate::decompose::Decompositions;
+use crate::lookups::{is_default_ignorable, width_mapping};
+use crate::recompose::Recompositions;
+use crate::strip_marks::MarkStripping;
+use core::{
+    char,
+    fmt::{self, Write},
+    iter::FusedIterator,
+    str::Chars,
+};
+
+/// The foldings applied to strings for search, such as full-text indexing,
+/// where the strings which only differ by their case, accents or width
+/// should match.
+///
+/// The steps are applied in this order, each one being optional:
+///
+/// 1. Compatibility decomposition (NFKD), or canonical decomposition (NFD)
+///    when it is disabled.
+/// 2. Removal of the `Default_Ignorable_Code_Point`s, such as U+00AD SOFT
+///    HYPHEN or U+200B ZERO WIDTH SPACE.
+/// 3. Width folding of the fullwidth and halfwidth forms.
+/// 4. Case folding.
+/// 5. Kana folding, from katakana to hiragana.
+/// 6. Decomposition again, and removal of combining marks as
+///    [`StripMarks`](crate::StripMarks) does.
+/// 7. Canonical composition (NFC).
+///
+/// [`SearchFold::new`] enables all of them. Whatever the configuration,
+/// strings only match if their foldings are equal, so the same configuration
+/// has to be used for the indexed strings and the queries.
+///
+/// ```rust
+/// use unicode_normalization::SearchFold;
+///
+/// let fold = SearchFold::new();
+/// assert_eq!(fold.fold("Cr\u{e8}me Br\u{fb}l\u{e9}e").to_string(), "creme brulee");
+/// assert_eq!(fold.fold("\u{ff34}\u{ff2f}\u{ff2b}\u{ff39}\u{ff2f}").to_string(), "tokyo");
+/// assert_eq!(fold.fold("\u{30ab}\u{30bf}\u{30ab}\u{30ca}").to_string(), "\u{304b}\u{305f}\u{304b}\u{306a}");
+/// assert_eq!(fold.fold("\u{ff76}\u{ff9e}").to_string(), "\u{304c}");
+///
+/// let fold = SearchFold::new().kana_folding(false);
+/// assert_eq!(fold.fold("\u{ff76}\u{ff9e}").to_string(), "\u{30ac}");
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SearchFold {
+    compatibility: bool,
+    remove_ignorables: bool,
+    width_folding: bool,
+    case_folding: Option<CaseFolding>,
+    kana_folding: bool,
+    mark_stripping: Option<MarkStripping>,
+}
+
+impl Default for SearchFold {
+    #[inline]
+    fn default() -> Self {
+        SearchFold::new()
+    }
+}
+
+impl SearchFold {
+    /// Create the default configuration, with every folding enabled: NFKD,
+    /// removal of the ignorables, width folding, full case folding, kana
+    /// folding and removal of the diacritics
+    /// ([`MarkStripping::Insignificant`]), which keeps the kana voicing marks.
+    #[inline]
+    pub fn new() -> Self {
+        SearchFold {
+            compatibility: true,
+            remove_ignorables: true,
+            width_folding: true,
+            case_folding: Some(CaseFolding::Full),
+            kana_folding: true,
+            mark_stripping: Some(MarkStripping::Insignificant),
+        }
+    }
+
+    /// Set whether to start with the compatibility decomposition (NFKD),
+    /// which also folds ligatures, superscripts, circled letters and the
+    /// like, rather than the canonical decomposition (NFD).
+    #[inline]
+    pub fn compatibility(mut self, compatibility: bool) -> Self {
+        self.compatibility = compatibility;
+        self
+    }
+
+    /// Set whether to remove the `Default_Ignorable_Code_Point`s.
+    #[inline]
+    pub fn remove_ignorables(mut self, remove_ignorables: bool) -> Self {
+        self.remove_ignorables = remove_ignorables;
+        self
+    }
+
+    /// Set whether to map the fullwidth and halfwidth forms to their usual
+    /// forms, such as U+FF21 FULLWIDTH LATIN CAPITAL LETTER A to `A` or
+    /// U+FF76 HALFWIDTH KATAKANA LETTER KA to U+30AB KATAKANA LETTER KA. The
+    /// compatibility decomposition already does it.
+    #[inline]
+    pub fn width_folding(mut self, width_folding: bool) -> Self {
+        self.width_folding = width_folding;
+        self
+    }
+
+    /// Set the case folding to apply, if any.
+    #[inline]
+    pub fn case_folding(mut self, case_folding: Option<CaseFolding>) -> Self {
+        self.case_folding = case_folding;
+        self
+    }
+
+    /// Set whether to map the katakana to the corresponding hiragana, such
+    /// as U+30AB KATAKANA LETTER KA to U+304B HIRAGANA LETTER KA. The
+    /// katakana which have no hiragana counterpart are left unchanged.
+    #[inline]
+    pub fn kana_folding(mut self, kana_folding: bool) -> Self {
+        self.kana_folding = kana_folding;
+        self
+    }
+
+    /// Set the combining marks to remove, if any.
+    #[inline]
+    pub fn mark_stripping(mut self, mark_stripping: Option<MarkStripping>) -> Self {
+        self.mark_stripping = mark_stripping;
+        self
+    }
+
+    /// Fold a string.
+    #[inline]
+    pub fn fold(self, s: &str) -> SearchFolds<Chars<'_>> {
+        SearchFolds::new(s.chars(), self)
+    }
+
+    #[inline]
+    fn decompose<I: Iterator<Item = char>>(self, iter: I) -> Decompositions<I> {
+        if self.compatibility {
+            Decompositions::new_compatible(iter)
+        } else {
+            Decompositions::new_canonical(iter)
+        }
+    }
+}
+
+/// Map a katakana to the corresponding hiragana.
+#[inline]
+fn fold_kana(c: char) -> char {
+    match c {
+        // From U+30A1 KATAKANA LETTER SMALL A to U+30F6 KATAKANA LETTER SMALL
+        // KE, and the iteration marks.
+        '\u{30A1}'..='\u{30F6}' | '\u{30FD}'..='\u{30FE}' => {
+            char::from_u32(c as u32 - 0x60).unwrap_or(c)
+        }
+        _ => c,
+    }
+}
+
+/// External iterator for a string's search folding.
+#[derive(Clone)]
+pub struct SearchFolds<I> {
+    iter: Recompositions<Stripped<I>>,
+}
+
+impl<I: Iterator<Item = char>> SearchFolds<I> {
+    /// Create a new iterator that applies the given search folding.
+    #[inline]
+    pub fn new(iter: I, fold: SearchFold) -> SearchFolds<I> {
+        let folds = Folds {
+            iter: fold.decompose(iter),
+            fold,
+            pending: &[],
+        };
+        SearchFolds {
+            iter: Recompositions::new_canonical(Stripped {
+                iter: fold.decompose(folds),
+                mark_stripping: fold.mark_stripping,
+            }),
+        }
+    }
+}
+
+impl<I: Iterator<Item = char>> Iterator for SearchFolds<I> {
+    type Item = char;
+
+    #[inline]
+    fn next(&mut self) -> Option<char> {
+        self.iter.next()
+    }
+}
+
+impl<I: Iterator<Item = char> + FusedIterator> FusedIterator for SearchFolds<I> {}
+
+impl<I: Iterator<Item = char> + Clone> fmt::Display for SearchFolds<I> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for c in self.clone() {
+            f.write_char(c)?;
+        }
+        Ok(())
+    }
+}
+
+/// The decomposed string with the ignorables removed and the width, case and
+/// kana foldings applied. These foldings don't preserve normalization, so the
+/// result is decomposed again before the marks are removed.
+#[derive(Clone)]
+struct Folds<I> {
+    iter: Decompositions<I>,
+    fold: SearchFold,
+    // The rest of a full case folding to more than one character.
+    pending: &'static [char],
+}
+
+impl<I: Iterator<Item = char>> Folds<I> {
+    #[inline]
+    fn kana(&self, c: char) -> char {
+        if self.fold.kana_folding {
+            fold_kana(c)
+        } else {
+            c
+        }
+    }
+}
+
+impl<I: Iterator<Item = char>> Iterator for Folds<I> {
+    type Item = char;
+
+    #[inline]
+    fn next(&mut self) -> Option<char> {
+        if let Some((&c, rest)) = self.pending.split_first() {
+            self.pending = rest;
+            return Some(self.kana(c));
+        }
+
+        loop {
+            let mut c = self.iter.next()?;
+            if self.fold.remove_ignorables && is_default_ignorable(c) {
+                continue;
+            }
+            if self.fold.width_folding {
+                c = width_mapping(c).unwrap_or(c);
+            }
+            if let Some(folding) = self.fold.case_folding {
+                c = match folding.fold(c) {
+                    Folded::One(c) => c,
+                    Folded::Many(s) => {
+                        self.pending = &s[1..];
+                        s[0]
+                    }
+                };
+            }
+            return Some(self.kana(c));
+        }
+    }
+}
+
+impl<I: Iterator<Item = char> + FusedIterator> FusedIterator for Folds<I> {}
+
+/// The folded string decomposed again, without the stripped marks.
+#[derive(Clone)]
+struct Stripped<I> {
+    iter: Decompositions<Folds<I>>,
+    mark_stripping: Option<MarkStripping>,
+}
+
+impl<I: Iterator<Item = char>> Iterator for Stripped<I> {
+    type Item = char;
+
+    #[inline]
+    fn next(&mut self) -> Option<char> {
+        match self.mark_stripping {
+            Some(stripping) => self.iter.by_ref().find(|&c| !stripping.strips(c)),
+            None => self.iter.next(),
+        }
+    }
+}
+
+impl<I: Iterator<Item = char> + FusedIterator> FusedIterator for Stripped<I> {}
+
+#[cfg(test)]
+mod tests {
+    use super::SearchFold;
+    use crate::{CaseFolding, MarkStripping};
+
+    #[cfg(not(feature = "std"))]
+    use alloc::string::String;
+
+    fn fold(s: &str, fold: SearchFold) -> String {
+        fold.fold(s).collect()
+    }
+
+    #[test]
+    fn test_search_fold() {
+        let all = SearchFold::new();
+        assert_eq!(fold("", all), "");
+        assert_eq!(fold("Hello, World", all), "hello, world");
+        assert_eq!(fold("Stra\u{df}e", all), "strasse");
+        assert_eq!(fold("\u{c5}ngstr\u{f6}m", all), "angstrom");
+        assert_eq!(fold("\u{212b}", all), "a");
+        assert_eq!(fold("\u{fb01}", all), "fi");
+        assert_eq!(fold("\u{2460}", all), "1");
+        assert_eq!(fold("\u{1f88}", all), "\u{3b1}\u{3b9}");
+        assert_eq!(fold("soft\u{ad}hyphen", all), "softhyphen");
+        assert_eq!(fold("\u{ff21}\u{ff22}\u{ff23}", all), "abc");
+        assert_eq!(fold("\u{3000}", all), " ");
+        // Width folding, kana folding and recomposition.
+        assert_eq!(fold("\u{ff76}\u{ff9e}", all), "\u{304c}");
+        assert_eq!(fold("\u{30f4}", all), "\u{3094}");
+        assert_eq!(fold("\u{30fe}", all), "\u{309e}");
+        assert_eq!(fold("\u{30fc}", all), "\u{30fc}");
+        // Hangul syllables and the kana voicing marks aren't diacritics.
+        assert_eq!(fold("\u{d55c}\u{ae00}", all), "\u{d55c}\u{ae00}");
+        assert_eq!(
+            fold("\u{30ac}", all.mark_stripping(Some(MarkStripping::All))),
+            "\u{304b}"
+        );
+    }
+
+    #[test]
+    fn test_search_fold_steps() {
+        let none = SearchFold::new()
+            .compatibility(false)
+            .remove_ignorables(false)
+            .width_folding(false)
+            .case_folding(None)
+            .kana_folding(false)
+            .mark_stripping(None);
+        assert_eq!(fold("A\u{301}\u{ad}", none), "\u{c1}\u{ad}");
+        assert_eq!(fold("\u{212b}", none), "\u{c5}");
+        assert_eq!(
+            fold("\u{fb01}\u{ff21}\u{30ab}", none),
+            "\u{fb01}\u{ff21}\u{30ab}"
+        );
+
+        assert_eq!(fold("\u{fb01}", none.compatibility(true)), "fi");
+        assert_eq!(fold("a\u{ad}b", none.remove_ignorables(true)), "ab");
+        assert_eq!(
+            fold("\u{ff21}\u{ff76}", none.width_folding(true)),
+            "A\u{30ab}"
+        );
+        assert_eq!(fold("\u{ffe3}", none.width_folding(true)), "\u{af}");
+        assert_eq!(
+            fold("I\u{df}", none.case_folding(Some(CaseFolding::Full))),
+            "iss"
+        );
+        assert_eq!(
+            fold(
+                "I\u{df}",
+                none.case_folding(Some(CaseFolding::SimpleTurkic))
+            ),
+            "\u{131}\u{df}"
+        );
+        assert_eq!(fold("\u{30ac}", none.kana_folding(true)), "\u{304c}");
+        assert_eq!(
+            fold(
+                "\u{e9}\u{915}\u{93f}",
+                none.mark_stripping(Some(MarkStripping::Insignificant))
+            ),
+            "e\u{915}\u{93f}"
+        );
+        assert_eq!(
+            fold(
+                "\u{e9}\u{915}\u{93f}",
+                none.mark_stripping(Some(MarkStripping::All))
+            ),
+            "e\u{915}"
+        );
+    }
+}
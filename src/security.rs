@@ -0,0 +1,423 @@
+// Copyright 2012-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Confusable detection, mixed-script detection and restriction levels, as
+//! described in [UTS #39](https://www.unicode.org/reports/tr39/), for
+//! catching spoofed identifiers.
+//!
+//! Two strings are confusable when they have the same [`skeleton`]: the NFD
+//! of the string, without default ignorable code points, with each character
+//! replaced by its prototype from `confusables.txt`, and normalized to NFD
+//! again. Skeletons are only meant to be compared with each other, not
+//! displayed.
+//!
+//! ```rust
+//! use unicode_normalization::security::{
+//!     is_confusable, is_mixed_script, restriction_level, RestrictionLevel,
+//! };
+//!
+//! // The second string has a Cyrillic "а".
+//! assert!(is_confusable("paypal", "p\u{430}ypal"));
+//! assert!(is_mixed_script("p\u{430}ypal"));
+//!
+//! assert_eq!(restriction_level("paypal"), RestrictionLevel::AsciiOnly);
+//! assert_eq!(restriction_level("p\u{430}ypal"), RestrictionLevel::MinimallyRestrictive);
+//! assert_eq!(restriction_level("abc\u{6f22}\u{5b57}"), RestrictionLevel::HighlyRestrictive);
+//! ```
+
+use crate::decompose::Decompositions;
+use crate::lookups::{
+    confusable_prototype, is_default_ignorable, is_identifier_allowed, script, script_extensions,
+};
+use crate::tables::{
+    RECOMMENDED_SCRIPTS, SCRIPT_BOPOMOFO, SCRIPT_COMMON, SCRIPT_CYRILLIC, SCRIPT_GREEK, SCRIPT_HAN,
+    SCRIPT_HANGUL, SCRIPT_HAN_WITH_BOPOMOFO, SCRIPT_HIRAGANA, SCRIPT_INHERITED, SCRIPT_JAPANESE,
+    SCRIPT_KATAKANA, SCRIPT_KOREAN, SCRIPT_LATIN, SCRIPT_WORDS,
+};
+use core::{
+    fmt::{self, Write},
+    iter::FusedIterator,
+    str::Chars,
+};
+
+/// Return the skeleton of a string, as defined in UTS #39 section 4.
+///
+/// ```rust
+/// use unicode_normalization::security::skeleton;
+///
+/// assert_eq!(skeleton("rn").collect::<String>(), "rn");
+/// assert_eq!(skeleton("m").collect::<String>(), "rn");
+/// assert_eq!(skeleton("\u{1d5c6}").collect::<String>(), "rn");
+/// ```
+#[inline]
+pub fn skeleton(s: &str) -> Skeleton<Chars<'_>> {
+    Skeleton::new(s.chars())
+}
+
+/// Return whether two strings are confusable, that is whether they have the
+/// same [`skeleton`].
+#[inline]
+pub fn is_confusable(a: &str, b: &str) -> bool {
+    a == b || skeleton(a).eq(skeleton(b))
+}
+
+/// External iterator for a string's skeleton.
+#[derive(Clone)]
+pub struct Skeleton<I> {
+    iter: Decompositions<Prototypes<I>>,
+}
+
+impl<I: Iterator<Item = char>> Skeleton<I> {
+    /// Create a new iterator for the skeleton of the given characters.
+    #[inline]
+    pub fn new(iter: I) -> Skeleton<I> {
+        Skeleton {
+            iter: Decompositions::new_canonical(Prototypes {
+                iter: Decompositions::new_canonical(iter),
+                pending: &[],
+            }),
+        }
+    }
+}
+
+impl<I: Iterator<Item = char>> Iterator for Skeleton<I> {
+    type Item = char;
+
+    #[inline]
+    fn next(&mut self) -> Option<char> {
+        self.iter.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<I: Iterator<Item = char> + FusedIterator> FusedIterator for Skeleton<I> {}
+
+impl<I: Iterator<Item = char> + Clone> fmt::Display for Skeleton<I> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for c in self.clone() {
+            f.write_char(c)?;
+        }
+        Ok(())
+    }
+}
+
+/// Replace characters with their prototypes and remove the default ignorable
+/// code points.
+#[derive(Clone)]
+struct Prototypes<I> {
+    iter: Decompositions<I>,
+    // The rest of a prototype of more than one character.
+    pending: &'static [char],
+}
+
+impl<I: Iterator<Item = char>> Iterator for Prototypes<I> {
+    type Item = char;
+
+    #[inline]
+    fn next(&mut self) -> Option<char> {
+        if let Some((&c, rest)) = self.pending.split_first() {
+            self.pending = rest;
+            return Some(c);
+        }
+
+        loop {
+            let c = self.iter.next()?;
+            if is_default_ignorable(c) {
+                continue;
+            }
+            return match confusable_prototype(c) {
+                Some(prototype) => {
+                    self.pending = &prototype[1..];
+                    Some(prototype[0])
+                }
+                None => Some(c),
+            };
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.pending.len(), None)
+    }
+}
+
+impl<I: Iterator<Item = char> + FusedIterator> FusedIterator for Prototypes<I> {}
+
+/// Return whether a string is mixed-script, that is whether its resolved
+/// script set is empty (UTS #39 section 5.1).
+///
+/// Characters of the Common and Inherited scripts go with any script, and
+/// Han characters go with Hiragana, Katakana, Hangul and Bopomofo ones.
+///
+/// ```rust
+/// use unicode_normalization::security::is_mixed_script;
+///
+/// assert!(!is_mixed_script("Circ1e"));
+/// assert!(!is_mixed_script("\u{3006}\u{5207}"));
+/// assert!(is_mixed_script("\u{421}ir\u{441}l\u{435}"));
+/// ```
+pub fn is_mixed_script(s: &str) -> bool {
+    s.chars()
+        .map(ScriptSet::of)
+        .fold(ScriptSet::ALL, ScriptSet::intersect)
+        .is_empty()
+}
+
+/// The restriction levels of UTS #39 section 5.2, from the most to the least
+/// restrictive.
+///
+/// A string which satisfies a level also satisfies the less restrictive ones,
+/// so levels can be compared: an application accepting moderately restrictive
+/// identifiers accepts those with `level <= ModeratelyRestrictive`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum RestrictionLevel {
+    /// Only ASCII characters of the identifier profile.
+    AsciiOnly,
+    /// Characters of a single script, and of the Common and Inherited
+    /// scripts.
+    SingleScript,
+    /// Single script, or Latin with Han, Hiragana and Katakana, or Latin with
+    /// Han and Bopomofo, or Latin with Han and Hangul.
+    HighlyRestrictive,
+    /// Latin with one other Recommended script of UAX #31, other than
+    /// Cyrillic and Greek.
+    ModeratelyRestrictive,
+    /// Any mix of scripts.
+    MinimallyRestrictive,
+    /// Characters outside of the identifier profile, which is the
+    /// `Identifier_Status` Allowed.
+    Unrestricted,
+}
+
+/// Return the most restrictive [`RestrictionLevel`] that a string satisfies.
+pub fn restriction_level(s: &str) -> RestrictionLevel {
+    if !s.chars().all(is_identifier_allowed) {
+        return RestrictionLevel::Unrestricted;
+    }
+    if s.is_ascii() {
+        return RestrictionLevel::AsciiOnly;
+    }
+
+    // The resolved script set of the string, and of its characters which
+    // can't be Latin.
+    let mut resolved = ScriptSet::ALL;
+    let mut not_latin = ScriptSet::ALL;
+    for c in s.chars() {
+        let set = ScriptSet::of(c);
+        resolved = resolved.intersect(set);
+        if !set.contains(SCRIPT_LATIN) {
+            not_latin = not_latin.intersect(set);
+        }
+    }
+
+    if !resolved.is_empty() {
+        RestrictionLevel::SingleScript
+    } else if not_latin.contains(SCRIPT_JAPANESE)
+        || not_latin.contains(SCRIPT_HAN_WITH_BOPOMOFO)
+        || not_latin.contains(SCRIPT_KOREAN)
+    {
+        RestrictionLevel::HighlyRestrictive
+    } else if !not_latin
+        .intersect(ScriptSet(RECOMMENDED_SCRIPTS))
+        .without(SCRIPT_CYRILLIC)
+        .without(SCRIPT_GREEK)
+        .is_empty()
+    {
+        RestrictionLevel::ModeratelyRestrictive
+    } else {
+        RestrictionLevel::MinimallyRestrictive
+    }
+}
+
+/// A set of scripts, indexed like the script tables.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct ScriptSet([u64; SCRIPT_WORDS]);
+
+impl ScriptSet {
+    pub(crate) const ALL: ScriptSet = ScriptSet([!0; SCRIPT_WORDS]);
+
+    /// Return the augmented script set of a character: its
+    /// `Script_Extensions`, with the writing systems which use Han, Hiragana,
+    /// Katakana, Hangul or Bopomofo.
+    pub(crate) fn of(c: char) -> ScriptSet {
+        let mut set = match script_extensions(c) {
+            Some(words) => ScriptSet(words),
+            None => {
+                let script = script(c);
+                if script == SCRIPT_COMMON || script == SCRIPT_INHERITED {
+                    return ScriptSet::ALL;
+                }
+                ScriptSet([0; SCRIPT_WORDS]).with(script)
+            }
+        };
+
+        if set.contains(SCRIPT_HAN) {
+            set = set
+                .with(SCRIPT_HAN_WITH_BOPOMOFO)
+                .with(SCRIPT_JAPANESE)
+                .with(SCRIPT_KOREAN);
+        }
+        if set.contains(SCRIPT_HIRAGANA) || set.contains(SCRIPT_KATAKANA) {
+            set = set.with(SCRIPT_JAPANESE);
+        }
+        if set.contains(SCRIPT_HANGUL) {
+            set = set.with(SCRIPT_KOREAN);
+        }
+        if set.contains(SCRIPT_BOPOMOFO) {
+            set = set.with(SCRIPT_HAN_WITH_BOPOMOFO);
+        }
+        set
+    }
+
+    #[inline]
+    pub(crate) fn contains(self, script: u8) -> bool {
+        self.0[script as usize / 64] & (1 << (script % 64)) != 0
+    }
+
+    #[inline]
+    fn with(mut self, script: u8) -> ScriptSet {
+        self.0[script as usize / 64] |= 1 << (script % 64);
+        self
+    }
+
+    #[inline]
+    fn without(mut self, script: u8) -> ScriptSet {
+        self.0[script as usize / 64] &= !(1 << (script % 64));
+        self
+    }
+
+    #[inline]
+    fn intersect(mut self, other: ScriptSet) -> ScriptSet {
+        for (word, other) in self.0.iter_mut().zip(other.0.iter()) {
+            *word &= other;
+        }
+        self
+    }
+
+    #[inline]
+    fn is_empty(self) -> bool {
+        self.0.iter().all(|&word| word == 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_confusable, is_mixed_script, restriction_level, RestrictionLevel};
+
+    #[cfg(not(feature = "std"))]
+    use alloc::string::String;
+
+    fn skeleton(s: &str) -> String {
+        super::skeleton(s).collect()
+    }
+
+    #[test]
+    fn test_skeleton() {
+        assert_eq!(skeleton("paypal"), "paypal");
+        assert_eq!(skeleton("p\u{430}yp\u{430}l"), "paypal");
+        assert_eq!(skeleton("m"), "rn");
+        assert_eq!(skeleton("0"), "O");
+        assert_eq!(skeleton("1"), "l");
+        assert_eq!(skeleton("I"), "l");
+        assert_eq!(skeleton(""), "");
+
+        // The default ignorable code points are removed, and the skeleton is
+        // in NFD.
+        assert_eq!(skeleton("pay\u{200b}pal"), "paypal");
+        assert_eq!(skeleton("\u{e9}"), "e\u{301}");
+        assert!(is_confusable("\u{e9}", "e\u{301}"));
+    }
+
+    #[test]
+    fn test_is_confusable() {
+        assert!(is_confusable("paypal", "p\u{430}yp\u{430}l"));
+        assert!(is_confusable("rn", "m"));
+        assert!(is_confusable("Il1", "lll"));
+        assert!(is_confusable("\u{1d5c6}", "m"));
+        assert!(!is_confusable("paypal", "paypa1x"));
+        assert!(!is_confusable("a", "b"));
+    }
+
+    #[test]
+    fn test_mixed_script() {
+        // Examples from UTS #39 section 5.1.
+        assert!(!is_mixed_script("Circle"));
+        assert!(!is_mixed_script(
+            "\u{421}\u{456}\u{433}\u{441}\u{4c0}\u{435}"
+        ));
+        assert!(is_mixed_script("\u{421}ir\u{441}l\u{435}"));
+        assert!(!is_mixed_script("Circ1e"));
+        assert!(!is_mixed_script(
+            "C\u{1d5c2}\u{1d5cb}\u{1d5bc}\u{1d5c5}\u{1d5be}"
+        ));
+        assert!(!is_mixed_script(
+            "\u{1d5a2}\u{1d5c2}\u{1d5cb}\u{1d5bc}\u{1d5c5}\u{1d5be}"
+        ));
+        assert!(!is_mixed_script("\u{3006}\u{5207}"));
+        assert!(!is_mixed_script("\u{306d}\u{30ac}"));
+        assert!(!is_mixed_script(""));
+
+        assert!(is_mixed_script("\u{30ac}\u{d55c}"));
+        assert!(!is_mixed_script("\u{5b57}\u{d55c}"));
+        // U+0660 ARABIC-INDIC DIGIT ZERO is used with Arabic and Thaana.
+        assert!(!is_mixed_script("\u{627}\u{660}"));
+        assert!(is_mixed_script("a\u{660}"));
+    }
+
+    #[test]
+    fn test_restriction_level() {
+        assert_eq!(restriction_level("paypal"), RestrictionLevel::AsciiOnly);
+        assert_eq!(restriction_level(""), RestrictionLevel::AsciiOnly);
+        assert_eq!(
+            restriction_level("caf\u{e9}"),
+            RestrictionLevel::SingleScript
+        );
+        assert_eq!(
+            restriction_level("\u{306d}\u{30ac}"),
+            RestrictionLevel::SingleScript
+        );
+        assert_eq!(
+            restriction_level("abc\u{3072}\u{3089}\u{6f22}"),
+            RestrictionLevel::HighlyRestrictive
+        );
+        assert_eq!(
+            restriction_level("abc\u{d55c}\u{5b57}"),
+            RestrictionLevel::HighlyRestrictive
+        );
+        assert_eq!(
+            restriction_level("abc\u{3105}\u{5b57}"),
+            RestrictionLevel::HighlyRestrictive
+        );
+        assert_eq!(
+            restriction_level("abc\u{5d0}"),
+            RestrictionLevel::ModeratelyRestrictive
+        );
+        assert_eq!(
+            restriction_level("abc\u{3b1}"),
+            RestrictionLevel::MinimallyRestrictive
+        );
+        assert_eq!(
+            restriction_level("abc\u{5d0}\u{3b1}"),
+            RestrictionLevel::MinimallyRestrictive
+        );
+        assert_eq!(
+            restriction_level("abc\u{30ac}\u{d55c}"),
+            RestrictionLevel::MinimallyRestrictive
+        );
+        assert_eq!(restriction_level("a b"), RestrictionLevel::Unrestricted);
+        assert_eq!(
+            restriction_level("abc\u{10330}"),
+            RestrictionLevel::Unrestricted
+        );
+        assert!(restriction_level("abc\u{5d0}") <= RestrictionLevel::ModeratelyRestrictive);
+    }
+}
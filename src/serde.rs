@@ -0,0 +1,200 @@
+// Copyright 2012-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Serde support, enabled by the `serde` feature.
+//!
+//! The normalized string types serialize as plain strings. The owned types
+//! normalize the strings they are deserialized from, while the borrowed types
+//! can only be deserialized from strings which are normalized already.
+//!
+//! For fields of type `String`, the functions in this module can be used with
+//! `#[serde(deserialize_with = "...")]`: the `deserialize_*` functions
+//! normalize the string, and the `validate_*` functions reject a string
+//! which isn't normalized with an error.
+//!
+//! ```rust
+//! use unicode_normalization::serde::{deserialize_nfc, validate_nfkc};
+//!
+//! let mut name = serde_json::Deserializer::from_str(r#""Jose\u0301""#);
+//! assert_eq!(deserialize_nfc(&mut name).unwrap(), "Jos\u{e9}");
+//!
+//! let mut handle = serde_json::Deserializer::from_str(r#""\u2460""#);
+//! assert!(validate_nfkc(&mut handle).is_err());
+//! ```
+
+use crate::in_place::{
+    normalize_nfc_in_place, normalize_nfd_in_place, normalize_nfkc_in_place,
+    normalize_nfkd_in_place,
+};
+use crate::quick_check::{is_nfc, is_nfd, is_nfkc, is_nfkd};
+use crate::string::{
+    NfcStr, NfcString, NfdStr, NfdString, NfkcStr, NfkcString, NfkdStr, NfkdString,
+};
+use ::serde::de::{Deserialize, Deserializer, Error, Unexpected};
+use ::serde::ser::{Serialize, Serializer};
+
+#[cfg(not(feature = "std"))]
+use alloc::string::String;
+
+macro_rules! serde_impls {
+    (
+        $str:ident,
+        $string:ident,
+        $is_normalized:ident,
+        $normalize_in_place:ident,
+        $expected:expr,
+        $(#[$deserialize_attr:meta])* $deserialize:ident,
+        $(#[$validate_attr:meta])* $validate:ident
+    ) => {
+        $(#[$deserialize_attr])*
+        pub fn $deserialize<'de, D>(deserializer: D) -> Result<String, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            let mut s = String::deserialize(deserializer)?;
+            $normalize_in_place(&mut s);
+            Ok(s)
+        }
+
+        $(#[$validate_attr])*
+        pub fn $validate<'de, D>(deserializer: D) -> Result<String, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            let s = String::deserialize(deserializer)?;
+            if $is_normalized(&s) {
+                Ok(s)
+            } else {
+                Err(D::Error::invalid_value(Unexpected::Str(&s), &$expected))
+            }
+        }
+
+        impl Serialize for $str {
+            #[inline]
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(self)
+            }
+        }
+
+        impl Serialize for $string {
+            #[inline]
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(self)
+            }
+        }
+
+        impl<'de: 'a, 'a> Deserialize<'de> for &'a $str {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let s = <&str>::deserialize(deserializer)?;
+                $str::new(s)
+                    .ok_or_else(|| D::Error::invalid_value(Unexpected::Str(s), &$expected))
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $string {
+            #[inline]
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                String::deserialize(deserializer).map($string::from)
+            }
+        }
+    };
+}
+
+serde_impls! {
+    NfcStr,
+    NfcString,
+    is_nfc,
+    normalize_nfc_in_place,
+    "a string in Unicode Normalization Form C",
+    /// Deserialize a string and convert it to Unicode Normalization Form C.
+    deserialize_nfc,
+    /// Deserialize a string, failing if it isn't in Unicode Normalization
+    /// Form C.
+    validate_nfc
+}
+
+serde_impls! {
+    NfkcStr,
+    NfkcString,
+    is_nfkc,
+    normalize_nfkc_in_place,
+    "a string in Unicode Normalization Form KC",
+    /// Deserialize a string and convert it to Unicode Normalization Form KC.
+    deserialize_nfkc,
+    /// Deserialize a string, failing if it isn't in Unicode Normalization
+    /// Form KC.
+    validate_nfkc
+}
+
+serde_impls! {
+    NfdStr,
+    NfdString,
+    is_nfd,
+    normalize_nfd_in_place,
+    "a string in Unicode Normalization Form D",
+    /// Deserialize a string and convert it to Unicode Normalization Form D.
+    deserialize_nfd,
+    /// Deserialize a string, failing if it isn't in Unicode Normalization
+    /// Form D.
+    validate_nfd
+}
+
+serde_impls! {
+    NfkdStr,
+    NfkdString,
+    is_nfkd,
+    normalize_nfkd_in_place,
+    "a string in Unicode Normalization Form KD",
+    /// Deserialize a string and convert it to Unicode Normalization Form KD.
+    deserialize_nfkd,
+    /// Deserialize a string, failing if it isn't in Unicode Normalization
+    /// Form KD.
+    validate_nfkd
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{deserialize_nfd, validate_nfc};
+    use crate::{NfcStr, NfcString, NfdString};
+    use serde_json::Deserializer;
+
+    #[cfg(not(feature = "std"))]
+    use alloc::string::ToString;
+
+    #[test]
+    fn test_helpers() {
+        let normalized = deserialize_nfd(&mut Deserializer::from_str(r#""\u00e1""#)).unwrap();
+        assert_eq!(normalized, "a\u{301}");
+        let validated = validate_nfc(&mut Deserializer::from_str(r#""\u00e1""#)).unwrap();
+        assert_eq!(validated, "\u{e1}");
+
+        let error = validate_nfc(&mut Deserializer::from_str(r#""a\u0301""#)).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid value: string \"a\\u{301}\", \
+             expected a string in Unicode Normalization Form C"
+        );
+    }
+
+    #[test]
+    fn test_types() {
+        let s: NfcString = serde_json::from_str(r#""a\u0301""#).unwrap();
+        assert_eq!(s, "\u{e1}");
+        assert_eq!(serde_json::to_string(&s).unwrap(), "\"\u{e1}\"");
+
+        let s: NfdString = serde_json::from_str("\"\u{e1}\"").unwrap();
+        assert_eq!(s, "a\u{301}");
+
+        let s: &NfcStr = serde_json::from_str("\"\u{e1}\"").unwrap();
+        assert_eq!(s, "\u{e1}");
+        assert_eq!(serde_json::to_string(s).unwrap(), "\"\u{e1}\"");
+        assert!(serde_json::from_str::<&NfcStr>("\"a\u{301}\"").is_err());
+    }
+}
@@ -0,0 +1,437 @@
+// Copyright 2012-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Incremental normalization of text which arrives in pieces.
+
+use crate::form::{normalize_to, Form, Sink};
+use crate::lookups::canonical_combining_class;
+use crate::normalize::{decompose_canonical, decompose_compatible};
+use crate::quick_check::IsNormalized;
+use crate::stream_safe::{classify_nonstarters, COMBINING_GRAPHEME_JOINER, MAX_NONSTARTERS};
+
+/// The length in bytes above which the pending text is cut.
+const MAX_PENDING: usize = 1024;
+
+#[cfg(not(feature = "std"))]
+use alloc::string::String;
+
+/// Normalizes text pushed to it chunk by chunk.
+///
+/// Text is normalized and emitted up to the last normalization boundary seen
+/// so far. Everything after it is kept in `pending` until a later chunk (or
+/// `finish`) completes the segment, since it may still reorder or compose
+/// with text that hasn't arrived yet.
+#[derive(Clone, Debug)]
+pub(crate) struct Incremental {
+    form: Form,
+    // If set, the input is passed through the Stream-Safe Text Process
+    // (UAX15-D4) first, and a Combining Grapheme Joiner is inserted when
+    // `pending` can't be cut otherwise, so that it stays bounded for any
+    // input.
+    stream_safe: bool,
+    nonstarter_count: usize,
+    pending: String,
+    // The length of the start of `pending` which `bound_pending` already
+    // found no place to cut in, so that it isn't searched again.
+    scanned: usize,
+}
+
+impl Incremental {
+    pub(crate) fn new(form: Form, stream_safe: bool) -> Self {
+        Incremental {
+            form,
+            stream_safe,
+            nonstarter_count: 0,
+            pending: String::new(),
+            scanned: 0,
+        }
+    }
+
+    /// Normalize the next chunk of text, appending everything that can no
+    /// longer change to `out`.
+    pub(crate) fn push_str<S: Sink + ?Sized>(&mut self, s: &str, out: &mut S) {
+        if !self.stream_safe {
+            self.push_segments(s, out);
+            return;
+        }
+
+        // This mirrors `StreamSafe::next`, but splits `s` around the inserted
+        // Combining Grapheme Joiners instead of yielding characters.
+        let mut start = 0;
+        for (i, c) in s.char_indices() {
+            let d = classify_nonstarters(c);
+            if self.nonstarter_count + d.leading_nonstarters > MAX_NONSTARTERS {
+                self.push_segments(&s[start..i], out);
+                let mut buf = [0; 4];
+                self.push_segments(COMBINING_GRAPHEME_JOINER.encode_utf8(&mut buf), out);
+                start = i;
+                self.nonstarter_count = 0;
+            }
+            if d.leading_nonstarters == d.decomposition_len {
+                self.nonstarter_count += d.decomposition_len;
+            } else {
+                self.nonstarter_count = d.trailing_nonstarters;
+            }
+        }
+        self.push_segments(&s[start..], out);
+    }
+
+    /// Keep `pending` under `MAX_PENDING` bytes, plus the size of the last
+    /// chunk, where the text allows it.
+    ///
+    /// `Form::is_boundary_before` only accepts starters that are normalized
+    /// themselves, so a long run of text such as Hangul syllables under NFD
+    /// has no boundary. Cutting `pending` before its last starter that can't
+    /// interact with the text before it leaves the output unchanged. Only a
+    /// run of non-starters, or of starters that may all compose with the
+    /// preceding character such as Hangul vowel jamo under NFC, has no such
+    /// starter. Like the Stream-Safe Text Process, a Combining Grapheme
+    /// Joiner is then inserted if the input is made stream-safe, which
+    /// blocks any composition across it.
+    fn bound_pending<S: Sink + ?Sized>(&mut self, out: &mut S) {
+        if self.pending.len() <= MAX_PENDING {
+            return;
+        }
+
+        let form = self.form;
+        let scanned = self.scanned;
+        let cut = self.pending[scanned..]
+            .char_indices()
+            .rev()
+            .map(|(i, c)| (scanned + i, c))
+            .find(|&(i, c)| i > 0 && is_segment_start(form, c))
+            .map(|(i, _)| i);
+        if let Some(cut) = cut {
+            normalize_to(form, &self.pending[..cut], out);
+            self.pending.drain(..cut);
+        }
+        // What is left starts with the last place to cut, if any.
+        self.scanned = self.pending.len();
+
+        if self.stream_safe && self.pending.len() > MAX_PENDING {
+            let mut buf = [0; 4];
+            normalize_to(form, &self.pending, out);
+            out.push_str(COMBINING_GRAPHEME_JOINER.encode_utf8(&mut buf));
+            self.clear_pending();
+            self.nonstarter_count = 0;
+        }
+    }
+
+    #[inline]
+    fn clear_pending(&mut self) {
+        self.pending.clear();
+        self.scanned = 0;
+    }
+
+    fn push_segments<S: Sink + ?Sized>(&mut self, s: &str, out: &mut S) {
+        let form = self.form;
+        let last = s
+            .char_indices()
+            .rev()
+            .find(|&(_, c)| form.is_boundary_before(c))
+            .map(|(i, _)| i);
+        let last = match last {
+            Some(last) => last,
+            None => {
+                self.pending.push_str(s);
+                self.bound_pending(out);
+                return;
+            }
+        };
+
+        let (complete, rest) = s.split_at(last);
+        if self.pending.is_empty() {
+            normalize_to(form, complete, out);
+        } else {
+            // The pending segment continues up to the first boundary in `s`.
+            let first = complete
+                .char_indices()
+                .find(|&(_, c)| form.is_boundary_before(c))
+                .map_or(complete.len(), |(i, _)| i);
+            self.pending.push_str(&complete[..first]);
+            normalize_to(form, &self.pending, out);
+            self.clear_pending();
+            normalize_to(form, &complete[first..], out);
+        }
+        self.pending.push_str(rest);
+        self.bound_pending(out);
+    }
+
+    /// The text after the last normalization boundary, which hasn't been
+    /// emitted yet.
+    #[inline]
+    pub(crate) fn pending(&self) -> &str {
+        &self.pending
+    }
+
+    /// Normalize and emit any pending text, as the input has ended.
+    ///
+    /// Afterwards, the normalizer is ready to process a new, unrelated input.
+    pub(crate) fn finish<S: Sink + ?Sized>(&mut self, out: &mut S) {
+        normalize_to(self.form, &self.pending, out);
+        self.clear_pending();
+        self.nonstarter_count = 0;
+    }
+}
+
+/// Whether `c` starts a segment in any context: the first character of its
+/// decomposition is a starter which neither reorders nor composes with the
+/// text before it. Unlike `Form::is_boundary_before`, this holds for starters
+/// which aren't normalized themselves, such as precomposed characters under
+/// NFD.
+fn is_segment_start(form: Form, c: char) -> bool {
+    let mut first = None;
+    let mut set_first = |d| {
+        if first.is_none() {
+            first = Some(d);
+        }
+    };
+    match form {
+        Form::Nfc | Form::Nfd => decompose_canonical(c, &mut set_first),
+        Form::Nfkc | Form::Nfkd => decompose_compatible(c, &mut set_first),
+    }
+    let first = first.unwrap_or(c);
+    canonical_combining_class(first) == 0
+        && match form {
+            Form::Nfc | Form::Nfkc => form.quick_check(first) != IsNormalized::Maybe,
+            Form::Nfd | Form::Nfkd => true,
+        }
+}
+
+/// A normalizer for text which arrives in pieces, such as network frames or
+/// keystrokes.
+///
+/// Unlike the iterator adapters, a `Normalizer` does not own its input. Each
+/// chunk passed to [`push_str`](Normalizer::push_str) is normalized up to the
+/// last normalization boundary seen so far, and the text after it is kept
+/// until a later chunk completes its segment, since it may still reorder or
+/// compose with what comes next. [`finish`](Normalizer::finish) marks the end
+/// of the text.
+///
+/// The output is the same as normalizing the concatenation of all the chunks
+/// at once.
+///
+/// The text held back is kept as it was pushed, and is only normalized once
+/// its segment is complete, rather than being kept as the decomposition and
+/// composition state of the iterators. This lets `push_str` copy the runs of
+/// text that are already normalized without decoding them. To bound its
+/// memory, the held back text is flushed up to its last character which
+/// can't interact with the text before it whenever it grows past 1 KiB, so
+/// it stays under 1 KiB plus the length of the last chunk. Only text without
+/// such a character is held back in full until the next boundary or
+/// `finish`: a run of combining marks, or for NFC and NFKC a run of
+/// characters which may all compose with the preceding one, such as Hangul
+/// vowel jamo.
+///
+/// ```rust
+/// use unicode_normalization::Normalizer;
+///
+/// let mut normalizer = Normalizer::nfc();
+/// let mut out = String::new();
+/// normalizer.push_str("Introduccio", &mut out);
+/// assert_eq!(out, "Introducci");
+/// normalizer.push_str("\u{301}n", &mut out);
+/// assert_eq!(out, "Introducci\u{f3}");
+/// normalizer.finish(&mut out);
+/// assert_eq!(out, "Introducci\u{f3}n");
+/// ```
+#[derive(Clone, Debug)]
+pub struct Normalizer {
+    inner: Incremental,
+}
+
+impl Normalizer {
+    #[inline]
+    fn new(form: Form) -> Self {
+        Normalizer {
+            inner: Incremental::new(form, false),
+        }
+    }
+
+    /// Create a normalizer to Unicode Normalization Form C.
+    #[inline]
+    pub fn nfc() -> Self {
+        Normalizer::new(Form::Nfc)
+    }
+
+    /// Create a normalizer to Unicode Normalization Form KC.
+    #[inline]
+    pub fn nfkc() -> Self {
+        Normalizer::new(Form::Nfkc)
+    }
+
+    /// Create a normalizer to Unicode Normalization Form D.
+    #[inline]
+    pub fn nfd() -> Self {
+        Normalizer::new(Form::Nfd)
+    }
+
+    /// Create a normalizer to Unicode Normalization Form KD.
+    #[inline]
+    pub fn nfkd() -> Self {
+        Normalizer::new(Form::Nfkd)
+    }
+
+    /// Normalize the next chunk of text, appending everything up to the last
+    /// normalization boundary to `out`.
+    #[inline]
+    pub fn push_str(&mut self, chunk: &str, out: &mut String) {
+        self.inner.push_str(chunk, out)
+    }
+
+    /// Normalize the text held back since the last normalization boundary and
+    /// append it to `out`, as the text has ended.
+    ///
+    /// Afterwards, the normalizer is ready to process a new, unrelated text.
+    #[inline]
+    pub fn finish(&mut self, out: &mut String) {
+        self.inner.finish(out)
+    }
+
+    /// The unnormalized text held back since the last normalization boundary.
+    #[inline]
+    pub fn pending(&self) -> &str {
+        self.inner.pending()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Incremental, Normalizer};
+    use crate::form::Form;
+    use crate::UnicodeNormalization;
+
+    #[cfg(not(feature = "std"))]
+    use alloc::string::String;
+
+    fn chunked(form: Form, stream_safe: bool, s: &str) -> String {
+        let mut normalizer = Incremental::new(form, stream_safe);
+        let mut out = String::new();
+        let mut start = 0;
+        for (i, _) in s.char_indices() {
+            if i % 3 == 0 {
+                normalizer.push_str(&s[start..i], &mut out);
+                start = i;
+            }
+        }
+        normalizer.push_str(&s[start..], &mut out);
+        normalizer.finish(&mut out);
+        out
+    }
+
+    #[test]
+    fn test_chunked() {
+        let inputs = [
+            "",
+            "abc",
+            "Introduccio\u{0301}n a Unicode.pdf",
+            "a\u{300}\u{305}\u{315}\u{5ae}b x\u{1e0b}\u{323}",
+            "\u{1e0b}\u{1c4}\u{2026}\u{2126}",
+            "\u{1100}\u{1161}\u{11a8} \u{1161}\u{1100}\u{1161}",
+            "\u{301}a\u{301}\u{301}",
+        ];
+        for s in &inputs {
+            assert_eq!(chunked(Form::Nfc, false, s), s.nfc().collect::<String>());
+            assert_eq!(chunked(Form::Nfd, false, s), s.nfd().collect::<String>());
+            assert_eq!(chunked(Form::Nfkc, false, s), s.nfkc().collect::<String>());
+            assert_eq!(chunked(Form::Nfkd, false, s), s.nfkd().collect::<String>());
+        }
+    }
+
+    #[test]
+    fn test_normalizer() {
+        let mut normalizer = Normalizer::nfd();
+        let mut out = String::new();
+        normalizer.push_str("a\u{1e0b}", &mut out);
+        // U+1E0B is not in NFD, so it may still reorder and isn't a boundary.
+        assert_eq!(out, "");
+        assert_eq!(normalizer.pending(), "a\u{1e0b}");
+        normalizer.push_str("\u{323}", &mut out);
+        assert_eq!(out, "");
+        normalizer.push_str("b", &mut out);
+        assert_eq!(out, "ad\u{323}\u{307}");
+        assert_eq!(normalizer.pending(), "b");
+        normalizer.finish(&mut out);
+        assert_eq!(out, "ad\u{323}\u{307}b");
+        assert_eq!(normalizer.pending(), "");
+    }
+
+    #[test]
+    fn test_bounded_pending() {
+        // Hangul syllables are only boundaries under NFD if the cut is exact.
+        let s = "\u{d55c}\u{ad6d}\u{c5b4}".repeat(1000);
+        let mut normalizer = Incremental::new(Form::Nfd, true);
+        let mut out = String::new();
+        normalizer.push_str(&s, &mut out);
+        assert!(normalizer.pending().len() <= super::MAX_PENDING);
+        normalizer.finish(&mut out);
+        assert_eq!(out, s.nfd().collect::<String>());
+        assert_eq!(chunked(Form::Nfkd, true, &s), s.nfkd().collect::<String>());
+
+        // Vowel jamo may all compose with the preceding character, so a
+        // Combining Grapheme Joiner is inserted.
+        let s = String::from("\u{1100}") + &"\u{1161}".repeat(1000);
+        let mut normalizer = Incremental::new(Form::Nfc, true);
+        let mut out = String::new();
+        for _ in 0..10 {
+            normalizer.push_str(&s, &mut out);
+            assert!(normalizer.pending().len() <= super::MAX_PENDING);
+        }
+        normalizer.finish(&mut out);
+        assert!(crate::is_nfc(&out));
+        assert_eq!(
+            out.replace('\u{34f}', ""),
+            s.repeat(10).nfc().collect::<String>()
+        );
+    }
+
+    #[test]
+    fn test_normalizer_bounded() {
+        let s = "\u{d55c}\u{ad6d}\u{c5b4}".repeat(1000);
+        let mut normalizer = Normalizer::nfd();
+        let mut out = String::new();
+        for c in s.chars() {
+            let mut buf = [0; 4];
+            normalizer.push_str(c.encode_utf8(&mut buf), &mut out);
+            assert!(normalizer.pending().len() <= super::MAX_PENDING + 3);
+        }
+        normalizer.finish(&mut out);
+        assert_eq!(out, s.nfd().collect::<String>());
+
+        // A run of marks has nowhere to be cut, and is held back in full.
+        let marks = "\u{301}\u{316}".repeat(1000);
+        let mut normalizer = Normalizer::nfc();
+        let mut out = String::new();
+        normalizer.push_str("a", &mut out);
+        for c in marks.chars() {
+            let mut buf = [0; 4];
+            normalizer.push_str(c.encode_utf8(&mut buf), &mut out);
+        }
+        assert_eq!(out, "");
+        assert_eq!(normalizer.pending().len(), 1 + marks.len());
+        normalizer.push_str("b", &mut out);
+        normalizer.finish(&mut out);
+        let s = String::from("a") + &marks + "b";
+        assert_eq!(out, s.nfc().collect::<String>());
+    }
+
+    #[test]
+    fn test_stream_safe() {
+        let s = "\u{342}".repeat(55) + &"\u{344}".repeat(3) + "a\u{301}" + &"\u{316}".repeat(40);
+        assert_eq!(
+            chunked(Form::Nfc, true, &s),
+            s.chars().stream_safe().nfc().collect::<String>()
+        );
+        assert_eq!(
+            chunked(Form::Nfkd, true, &s),
+            s.chars().stream_safe().nfkd().collect::<String>()
+        );
+    }
+}
@@ -8,7 +8,7 @@ use crate::normalize::{hangul_decomposition_length, is_hangul_syllable};
 use crate::tables::stream_safe_leading_nonstarters;
 
 pub(crate) const MAX_NONSTARTERS: usize = 30;
-const COMBINING_GRAPHEME_JOINER: char = '\u{034F}';
+pub(crate) const COMBINING_GRAPHEME_JOINER: char = '\u{034F}';
 
 /// [UAX15-D4]: This iterator keeps track of how many non-starters there have been
 /// since the last starter in *NFKD* and will emit a Combining Grapheme Joiner
@@ -0,0 +1,516 @@
+// Copyright 2012-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! String types which are known to be in a normalization form.
+
+use crate::form::{normalize_to, Form};
+use crate::in_place::{
+    normalize_nfc_in_place, normalize_nfd_in_place, normalize_nfkc_in_place,
+    normalize_nfkd_in_place,
+};
+use crate::quick_check::{is_nfc, is_nfd, is_nfkc, is_nfkd};
+use core::borrow::Borrow;
+use core::fmt;
+use core::ops::Deref;
+use tinyvec::TinyVec;
+
+#[cfg(not(feature = "std"))]
+use alloc::{borrow::ToOwned, string::String};
+
+/// Append `s` to the normalized string `out`, normalizing the result.
+///
+/// Text on either side of the join can only reorder or compose across it up
+/// to the last normalization boundary in `out` and the first one in `s`, so
+/// only the window between them is renormalized. If `s` is already
+/// normalized, the rest of it is copied as is.
+fn append(out: &mut String, s: &str, form: Form, s_is_normalized: bool) {
+    let end = s
+        .char_indices()
+        .find(|&(_, c)| form.is_boundary_before(c))
+        .map_or(s.len(), |(i, _)| i);
+    if end > 0 {
+        let start = out
+            .char_indices()
+            .rev()
+            .find(|&(_, c)| form.is_boundary_before(c))
+            .map_or(0, |(i, _)| i);
+        let window: TinyVec<[char; 16]> = out[start..].chars().chain(s[..end].chars()).collect();
+        out.truncate(start);
+        form.normalize_chars(window.into_iter(), |c| out.push(c));
+    }
+    if s_is_normalized {
+        out.push_str(&s[end..]);
+    } else {
+        normalize_to(form, &s[end..], out);
+    }
+}
+
+macro_rules! normalized_string {
+    (
+        $(#[$str_attr:meta])* $str:ident,
+        $(#[$string_attr:meta])* $string:ident,
+        $form:expr,
+        $is_normalized:ident,
+        $normalize_in_place:ident,
+        $(#[$concat_attr:meta])* $concat:ident
+    ) => {
+        $(#[$str_attr])*
+        #[derive(PartialEq, Eq, PartialOrd, Ord, Hash)]
+        #[repr(transparent)]
+        pub struct $str(str);
+
+        impl $str {
+            /// Return `s` as this type if it is normalized, or `None` if it
+            /// isn't.
+            #[inline]
+            pub fn new(s: &str) -> Option<&$str> {
+                if $is_normalized(s) {
+                    Some($str::from_str_unchecked(s))
+                } else {
+                    None
+                }
+            }
+
+            #[inline]
+            #[allow(unsafe_code)]
+            fn from_str_unchecked(s: &str) -> &$str {
+                // Safety: the type is a `repr(transparent)` wrapper around
+                // `str`, so the pointer casts keep the same layout and
+                // metadata.
+                unsafe { &*(s as *const str as *const $str) }
+            }
+
+            /// Returns the normalized string as a `str`.
+            #[inline]
+            pub fn as_str(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl Deref for $str {
+            type Target = str;
+
+            #[inline]
+            fn deref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl AsRef<str> for $str {
+            #[inline]
+            fn as_ref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl ToOwned for $str {
+            type Owned = $string;
+
+            #[inline]
+            fn to_owned(&self) -> $string {
+                $string(self.0.to_owned())
+            }
+        }
+
+        impl PartialEq<str> for $str {
+            #[inline]
+            fn eq(&self, other: &str) -> bool {
+                &self.0 == other
+            }
+        }
+
+        impl<'a> PartialEq<&'a str> for $str {
+            #[inline]
+            fn eq(&self, other: &&'a str) -> bool {
+                &self.0 == *other
+            }
+        }
+
+        impl fmt::Debug for $str {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                self.0.fmt(f)
+            }
+        }
+
+        impl fmt::Display for $str {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                self.0.fmt(f)
+            }
+        }
+
+        $(#[$string_attr])*
+        #[derive(Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub struct $string(String);
+
+        impl $string {
+            /// Create a new empty string.
+            #[inline]
+            pub fn new() -> $string {
+                $string(String::new())
+            }
+
+            /// Create a string holding the normalization of `s`.
+            #[inline]
+            pub fn normalize(s: &str) -> $string {
+                let mut out = String::with_capacity(s.len());
+                normalize_to($form, s, &mut out);
+                $string(out)
+            }
+
+            /// Return `s` as this type if it is normalized, or give it back if
+            /// it isn't.
+            #[inline]
+            pub fn from_normalized(s: String) -> Result<$string, String> {
+                if $is_normalized(&s) {
+                    Ok($string(s))
+                } else {
+                    Err(s)
+                }
+            }
+
+            /// Append `s` to the string, normalizing the result.
+            ///
+            /// Only the text between the last normalization boundary of the
+            /// string and the first one in `s` is normalized as a whole; the
+            /// rest of `s` is normalized on its own.
+            #[inline]
+            pub fn push_str(&mut self, s: &str) {
+                append(&mut self.0, s, $form, false)
+            }
+
+            /// Append the normalized string `s` to the string.
+            ///
+            /// Like [`push_str`](Self::push_str), but the text of `s` after
+            /// its first normalization boundary is copied without checking it.
+            #[inline]
+            pub fn push_normalized(&mut self, s: &$str) {
+                append(&mut self.0, s, $form, true)
+            }
+
+            /// Returns the normalized string as a `str`.
+            #[inline]
+            pub fn as_str(&self) -> &str {
+                &self.0
+            }
+
+            /// Unwraps the normalized `String`.
+            #[inline]
+            pub fn into_string(self) -> String {
+                self.0
+            }
+        }
+
+        impl Deref for $string {
+            type Target = $str;
+
+            #[inline]
+            fn deref(&self) -> &$str {
+                $str::from_str_unchecked(&self.0)
+            }
+        }
+
+        impl AsRef<str> for $string {
+            #[inline]
+            fn as_ref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl AsRef<$str> for $string {
+            #[inline]
+            fn as_ref(&self) -> &$str {
+                self
+            }
+        }
+
+        impl Borrow<$str> for $string {
+            #[inline]
+            fn borrow(&self) -> &$str {
+                self
+            }
+        }
+
+        impl<'a> From<&'a str> for $string {
+            /// Normalizes `s`.
+            #[inline]
+            fn from(s: &'a str) -> $string {
+                $string::normalize(s)
+            }
+        }
+
+        impl From<String> for $string {
+            /// Normalizes `s`, reusing its allocation.
+            #[inline]
+            fn from(mut s: String) -> $string {
+                $normalize_in_place(&mut s);
+                $string(s)
+            }
+        }
+
+        impl<'a> From<&'a $str> for $string {
+            #[inline]
+            fn from(s: &'a $str) -> $string {
+                s.to_owned()
+            }
+        }
+
+        impl From<$string> for String {
+            #[inline]
+            fn from(s: $string) -> String {
+                s.0
+            }
+        }
+
+        impl PartialEq<str> for $string {
+            #[inline]
+            fn eq(&self, other: &str) -> bool {
+                self.0 == other
+            }
+        }
+
+        impl<'a> PartialEq<&'a str> for $string {
+            #[inline]
+            fn eq(&self, other: &&'a str) -> bool {
+                self.0 == *other
+            }
+        }
+
+        impl fmt::Debug for $string {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                self.0.fmt(f)
+            }
+        }
+
+        impl fmt::Display for $string {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                self.0.fmt(f)
+            }
+        }
+
+        $(#[$concat_attr])*
+        #[inline]
+        pub fn $concat(a: &$str, b: &$str) -> $string {
+            let mut out = String::with_capacity(a.len() + b.len());
+            out.push_str(a);
+            append(&mut out, b, $form, true);
+            $string(out)
+        }
+    };
+}
+
+normalized_string! {
+    /// A borrowed string which is in Unicode Normalization Form C.
+    ///
+    /// It dereferences to `str`, and can be obtained by validating a `str`
+    /// with [`NfcStr::new`] or by borrowing an [`NfcString`].
+    NfcStr,
+    /// An owned string which is in Unicode Normalization Form C.
+    ///
+    /// It can only be created by normalizing text, or by validating text that
+    /// is normalized already, so holding one proves the text is in NFC.
+    ///
+    /// ```rust
+    /// use unicode_normalization::{NfcStr, NfcString};
+    ///
+    /// let mut s = NfcString::from("Introduccio\u{301}n");
+    /// assert_eq!(s, "Introducci\u{f3}n");
+    ///
+    /// // Appending renormalizes the text around the join.
+    /// s.push_str("\u{301}");
+    /// assert_eq!(s, "Introducci\u{f3}\u{144}");
+    ///
+    /// let borrowed: &NfcStr = &s;
+    /// assert_eq!(NfcStr::new("Introducci\u{f3}\u{144}"), Some(borrowed));
+    /// assert_eq!(NfcStr::new("n\u{301}"), None);
+    /// ```
+    NfcString,
+    Form::Nfc,
+    is_nfc,
+    normalize_nfc_in_place,
+    /// Concatenate two strings in Unicode Normalization Form C.
+    ///
+    /// The concatenation of normalized strings isn't necessarily normalized,
+    /// but only the text around the join needs to be normalized again: from
+    /// the last normalization boundary in `a` to the first one in `b`.
+    ///
+    /// ```rust
+    /// use unicode_normalization::{concat_nfc, NfcStr};
+    ///
+    /// let a = NfcStr::new("Introduccio").unwrap();
+    /// let b = NfcStr::new("\u{301}n").unwrap();
+    /// assert_eq!(concat_nfc(a, b), "Introducci\u{f3}n");
+    /// ```
+    concat_nfc
+}
+
+normalized_string! {
+    /// A borrowed string which is in Unicode Normalization Form KC.
+    ///
+    /// See [`NfcStr`] for details.
+    NfkcStr,
+    /// An owned string which is in Unicode Normalization Form KC.
+    ///
+    /// See [`NfcString`] for details.
+    NfkcString,
+    Form::Nfkc,
+    is_nfkc,
+    normalize_nfkc_in_place,
+    /// Concatenate two strings in Unicode Normalization Form KC.
+    ///
+    /// See [`concat_nfc`] for details.
+    concat_nfkc
+}
+
+normalized_string! {
+    /// A borrowed string which is in Unicode Normalization Form D.
+    ///
+    /// See [`NfcStr`] for details.
+    NfdStr,
+    /// An owned string which is in Unicode Normalization Form D.
+    ///
+    /// See [`NfcString`] for details.
+    NfdString,
+    Form::Nfd,
+    is_nfd,
+    normalize_nfd_in_place,
+    /// Concatenate two strings in Unicode Normalization Form D.
+    ///
+    /// See [`concat_nfc`] for details.
+    concat_nfd
+}
+
+normalized_string! {
+    /// A borrowed string which is in Unicode Normalization Form KD.
+    ///
+    /// See [`NfcStr`] for details.
+    NfkdStr,
+    /// An owned string which is in Unicode Normalization Form KD.
+    ///
+    /// See [`NfcString`] for details.
+    NfkdString,
+    Form::Nfkd,
+    is_nfkd,
+    normalize_nfkd_in_place,
+    /// Concatenate two strings in Unicode Normalization Form KD.
+    ///
+    /// See [`concat_nfc`] for details.
+    concat_nfkd
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        concat_nfc, concat_nfd, concat_nfkc, concat_nfkd, NfcStr, NfcString, NfdStr, NfdString,
+        NfkcStr, NfkcString, NfkdStr, NfkdString,
+    };
+
+    #[cfg(not(feature = "std"))]
+    use alloc::{borrow::ToOwned, string::String};
+
+    #[test]
+    fn test_construct() {
+        assert_eq!(NfcString::from("a\u{301}"), "\u{e1}");
+        assert_eq!(NfdString::from("\u{e1}"), "a\u{301}");
+        assert_eq!(NfkcString::from("\u{fb01}\u{2126}"), "fi\u{3a9}");
+        assert_eq!(
+            NfkdString::from(String::from("\u{1e0b}\u{323}")),
+            "d\u{323}\u{307}"
+        );
+
+        assert!(NfcString::from_normalized(String::from("\u{e1}")).is_ok());
+        assert_eq!(
+            NfcString::from_normalized(String::from("a\u{301}")),
+            Err(String::from("a\u{301}"))
+        );
+        assert_eq!(NfcStr::new("\u{e1}").map(NfcStr::as_str), Some("\u{e1}"));
+        assert_eq!(NfdStr::new("\u{e1}"), None);
+
+        let borrowed = NfdStr::new("a\u{301}").unwrap();
+        let owned: NfdString = borrowed.to_owned();
+        assert_eq!(&*owned, borrowed);
+        assert_eq!(owned.len(), 3);
+        assert_eq!(String::from(owned), "a\u{301}");
+    }
+
+    #[test]
+    fn test_push_str() {
+        let mut s = NfcString::new();
+        for part in &[
+            "Introduccio",
+            "\u{301}",
+            "n",
+            "\u{323}\u{307}",
+            "d",
+            "\u{307}",
+        ] {
+            s.push_str(part);
+        }
+        assert_eq!(s, "Introducci\u{f3}\u{1e47}\u{307}\u{1e0b}");
+
+        // The marks only reorder when both are there.
+        let mut s = NfdString::from("a\u{301}");
+        s.push_str("\u{316}b");
+        assert_eq!(s, "a\u{316}\u{301}b");
+
+        let mut s = NfkcString::from("x");
+        s.push_str("\u{2026}\u{1100}");
+        s.push_str("\u{1161}");
+        assert_eq!(s, "x...\u{ac00}");
+    }
+
+    #[test]
+    fn test_concat() {
+        let nfc = |s| NfcStr::new(s).unwrap();
+        assert_eq!(concat_nfc(nfc("abc"), nfc("def")), "abcdef");
+        assert_eq!(concat_nfc(nfc(""), nfc("\u{301}")), "\u{301}");
+        assert_eq!(concat_nfc(nfc("a"), nfc("\u{301}b")), "\u{e1}b");
+        // The window starts at the last boundary, before the composed character.
+        assert_eq!(
+            concat_nfc(nfc("x\u{1e0b}"), nfc("\u{323}y")),
+            "x\u{1e0d}\u{307}y"
+        );
+        assert_eq!(
+            concat_nfc(nfc("\u{1100}"), nfc("\u{1161}\u{11a8}")),
+            "\u{ac01}"
+        );
+
+        let nfd = |s| NfdStr::new(s).unwrap();
+        assert_eq!(
+            concat_nfd(nfd("a\u{301}"), nfd("\u{316}b\u{301}")),
+            "a\u{316}\u{301}b\u{301}"
+        );
+
+        let nfkc = |s| NfkcStr::new(s).unwrap();
+        // "fi" is the NFKC of U+FB01 LATIN SMALL LIGATURE FI.
+        assert_eq!(concat_nfkc(nfkc("fi"), nfkc("\u{301}")), "f\u{ed}");
+        assert_eq!(
+            concat_nfkc(nfkc("x\u{301}"), nfkc("\u{316}y")),
+            "x\u{316}\u{301}y"
+        );
+        assert_eq!(
+            concat_nfkc(nfkc("x\u{1100}"), nfkc("\u{1161}\u{11a8}y")),
+            "x\u{ac01}y"
+        );
+
+        let nfkd = |s| NfkdStr::new(s).unwrap();
+        assert_eq!(
+            concat_nfkd(nfkd("fi\u{301}"), nfkd("\u{316}")),
+            "fi\u{316}\u{301}"
+        );
+        assert_eq!(
+            concat_nfkd(nfkd("x\u{1100}"), nfkd("\u{1161}\u{11a8}y")),
+            "x\u{1100}\u{1161}\u{11a8}y"
+        );
+
+        let mut s = NfkcString::from("x\u{1100}");
+        s.push_normalized(NfkcStr::new("\u{1161}.").unwrap());
+        assert_eq!(s, "x\u{ac00}.");
+    }
+}
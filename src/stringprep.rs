@@ -0,0 +1,268 @@
+// Copyright 2012-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The stringprep algorithm of [RFC 3454](https://www.rfc-editor.org/rfc/rfc3454)
+//! with its Nameprep ([RFC 3491](https://www.rfc-editor.org/rfc/rfc3491))
+//! and SASLprep ([RFC 4013](https://www.rfc-editor.org/rfc/rfc4013))
+//! profiles, for the protocols which still use them, such as IDNA2003, LDAP
+//! and SCRAM authentication.
+//!
+//! A profile maps some characters (removing them, case folding or mapping
+//! spaces), normalizes the result to NFKC and then rejects prohibited
+//! characters and bidirectional text which doesn't follow the rules of
+//! RFC 3454 section 6.
+//!
+//! Stringprep is pinned to Unicode 3.2, so unlike the rest of this crate it
+//! doesn't follow [`UNICODE_VERSION`](crate::UNICODE_VERSION):
+//!
+//! * The mapping, prohibition and bidirectional tables are those of the RFC.
+//! * Code points which were unassigned in Unicode 3.2 are rejected in stored
+//!   strings, or passed through unchanged in queries, depending on the
+//!   [`Unassigned`] policy.
+//! * The normalization reverts the decomposition corrections made after
+//!   Unicode 3.2 (listed in `NormalizationCorrections.txt`), so for example
+//!   U+2F868 CJK COMPATIBILITY IDEOGRAPH-2F868 still normalizes to U+2136A.
+//!
+//! ```rust
+//! use unicode_normalization::stringprep::{self, Error};
+//!
+//! // Examples from RFC 4013 section 3.
+//! assert_eq!(stringprep::saslprep("I\u{ad}X").unwrap(), "IX");
+//! assert_eq!(stringprep::saslprep("\u{2168}").unwrap(), "IX");
+//! assert_eq!(stringprep::saslprep("\u{7}"), Err(Error::Prohibited('\u{7}')));
+//! assert_eq!(stringprep::saslprep("\u{627}1"), Err(Error::Bidi));
+//!
+//! assert_eq!(stringprep::nameprep("Stra\u{df}e.DE").unwrap(), "strasse.de");
+//! ```
+
+use crate::lookups::{
+    is_stringprep_l_cat, is_stringprep_prohibited, is_stringprep_rand_al_cat,
+    is_stringprep_unassigned, stringprep_case_fold,
+};
+use crate::recompose::Recompositions;
+use crate::tables::{
+    is_stringprep_mapped_to_nothing, is_stringprep_non_ascii_space,
+    stringprep_normalization_correction,
+};
+use core::fmt;
+
+#[cfg(not(feature = "std"))]
+use alloc::string::String;
+
+/// The stringprep profiles.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Profile {
+    /// The Nameprep profile of RFC 3491, for the labels of internationalized
+    /// domain names in IDNA2003: removes the characters of table B.1, case
+    /// folds with table B.2 and prohibits the non-ASCII spaces (table C.1.2)
+    /// and the characters of tables C.2.2 to C.9.
+    Nameprep,
+    /// The SASLprep profile of RFC 4013, for user names and passwords: maps
+    /// the non-ASCII spaces to U+0020 SPACE, removes the characters of table
+    /// B.1 and prohibits the controls and the characters of tables C.2.2 to
+    /// C.9. Case is preserved.
+    Saslprep,
+}
+
+/// How to handle code points which were unassigned in Unicode 3.2 (table
+/// A.1 of RFC 3454).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Unassigned {
+    /// Fail with [`Error::Unassigned`], as required for stored strings.
+    Prohibit,
+    /// Copy them to the output unchanged, as allowed for queries. They are
+    /// neither mapped nor normalized, as in Unicode 3.2.
+    Allow,
+}
+
+impl Profile {
+    /// Prepare a string with the profile.
+    pub fn prepare(self, s: &str, unassigned: Unassigned) -> Result<String, Error> {
+        let mut mapped = String::with_capacity(s.len());
+        for c in s.chars() {
+            if unassigned == Unassigned::Prohibit && is_stringprep_unassigned(c) {
+                return Err(Error::Unassigned(c));
+            }
+            if is_stringprep_mapped_to_nothing(c) {
+                continue;
+            }
+            match self {
+                Profile::Nameprep => match stringprep_case_fold(c) {
+                    Some(folded) => mapped.extend(folded),
+                    None => mapped.push(c),
+                },
+                Profile::Saslprep if is_stringprep_non_ascii_space(c) => mapped.push(' '),
+                Profile::Saslprep => mapped.push(c),
+            }
+        }
+
+        let prepared = normalize(&mapped);
+
+        for c in prepared.chars() {
+            if is_stringprep_non_ascii_space(c)
+                || is_stringprep_prohibited(c)
+                || (self == Profile::Saslprep && c.is_ascii_control())
+            {
+                return Err(Error::Prohibited(c));
+            }
+        }
+
+        if prepared.chars().any(is_stringprep_rand_al_cat)
+            && (prepared.chars().any(is_stringprep_l_cat)
+                || !prepared.starts_with(is_stringprep_rand_al_cat)
+                || !prepared.ends_with(is_stringprep_rand_al_cat))
+        {
+            return Err(Error::Bidi);
+        }
+
+        Ok(prepared)
+    }
+}
+
+/// Prepare a domain name label, or a whole domain name, with the Nameprep
+/// profile. Unassigned code points are allowed, as in the IDNA2003 lookups.
+pub fn nameprep(s: &str) -> Result<String, Error> {
+    Profile::Nameprep.prepare(s, Unassigned::Allow)
+}
+
+/// Prepare a user name or a password with the SASLprep profile.
+/// Unassigned code points are prohibited, as in the stored strings of SCRAM
+/// authentication.
+pub fn saslprep(s: &str) -> Result<String, Error> {
+    Profile::Saslprep.prepare(s, Unassigned::Prohibit)
+}
+
+/// Normalize to the NFKC of Unicode 3.2. The unassigned code points were
+/// starters without decompositions or compositions, so the text between
+/// them is normalized separately.
+fn normalize(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(i) = rest.find(is_stringprep_unassigned) {
+        let c = rest[i..].chars().next().unwrap();
+        out.extend(Recompositions::new_compatible(
+            rest[..i].chars().map(correct),
+        ));
+        out.push(c);
+        rest = &rest[i + c.len_utf8()..];
+    }
+    out.extend(Recompositions::new_compatible(rest.chars().map(correct)));
+    out
+}
+
+/// Revert a decomposition correction made after Unicode 3.2.
+#[inline]
+fn correct(c: char) -> char {
+    stringprep_normalization_correction(c).unwrap_or(c)
+}
+
+/// An error returned when a string can't be prepared by a stringprep profile.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Error {
+    /// The string contains a code point which was unassigned in Unicode 3.2,
+    /// with [`Unassigned::Prohibit`].
+    Unassigned(char),
+    /// The prepared string contains a code point which the profile
+    /// prohibits.
+    Prohibited(char),
+    /// The prepared string contains right-to-left characters and breaks the
+    /// rules of RFC 3454 section 6.
+    Bidi,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Unassigned(c) => write!(f, "unassigned code point {:?}", c),
+            Error::Prohibited(c) => write!(f, "prohibited code point {:?}", c),
+            Error::Bidi => f.write_str("prohibited bidirectional text"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+#[cfg(test)]
+mod tests {
+    use super::{nameprep, saslprep, Error, Profile, Unassigned};
+
+    #[test]
+    fn test_saslprep() {
+        // Examples from RFC 4013 section 3.
+        assert_eq!(saslprep("I\u{ad}X").unwrap(), "IX");
+        assert_eq!(saslprep("user").unwrap(), "user");
+        assert_eq!(saslprep("USER").unwrap(), "USER");
+        assert_eq!(saslprep("\u{aa}").unwrap(), "a");
+        assert_eq!(saslprep("\u{2168}").unwrap(), "IX");
+        assert_eq!(saslprep("\u{7}"), Err(Error::Prohibited('\u{7}')));
+        assert_eq!(saslprep("\u{627}\u{31}"), Err(Error::Bidi));
+
+        assert_eq!(saslprep("pass\u{a0}word").unwrap(), "pass word");
+        assert_eq!(saslprep("pass\u{200b}word").unwrap(), "password");
+        assert_eq!(
+            saslprep("\u{627}\u{31}\u{628}").unwrap(),
+            "\u{627}\u{31}\u{628}"
+        );
+        assert_eq!(saslprep("\u{e000}"), Err(Error::Prohibited('\u{e000}')));
+        assert_eq!(saslprep("a\u{221}"), Err(Error::Unassigned('\u{221}')));
+        assert_eq!(saslprep("").unwrap(), "");
+    }
+
+    #[test]
+    fn test_nameprep() {
+        assert_eq!(nameprep("CAF\u{c9}").unwrap(), "caf\u{e9}");
+        assert_eq!(nameprep("CAFE\u{301}").unwrap(), "caf\u{e9}");
+        assert_eq!(nameprep("\u{3a3}\u{3a3}").unwrap(), "\u{3c3}\u{3c3}");
+        assert_eq!(nameprep("\u{130}").unwrap(), "i\u{307}");
+        assert_eq!(nameprep("\u{3392}").unwrap(), "mhz");
+        assert_eq!(nameprep("\u{fb01}").unwrap(), "fi");
+        assert_eq!(nameprep("foo\u{feff}bar").unwrap(), "foobar");
+        assert_eq!(nameprep("foo bar").unwrap(), "foo bar");
+        assert_eq!(nameprep("foo\u{a0}bar").unwrap(), "foo bar");
+        assert_eq!(
+            nameprep("foo\u{1680}bar"),
+            Err(Error::Prohibited('\u{1680}'))
+        );
+        assert_eq!(nameprep("foo\u{85}bar"), Err(Error::Prohibited('\u{85}')));
+        assert_eq!(
+            nameprep("foo\u{2ff0}bar"),
+            Err(Error::Prohibited('\u{2ff0}'))
+        );
+        assert_eq!(nameprep("\u{5d0}a\u{5d1}"), Err(Error::Bidi));
+        assert_eq!(nameprep("\u{5d0}\u{5d1}").unwrap(), "\u{5d0}\u{5d1}");
+    }
+
+    #[test]
+    fn test_unicode_3_2() {
+        // Decomposition corrections made after Unicode 3.2 are reverted.
+        assert_eq!(nameprep("\u{2f868}").unwrap(), "\u{2136a}");
+        assert_eq!(nameprep("\u{2f9bf}").unwrap(), "\u{4d57}");
+        assert_eq!(nameprep("\u{f951}").unwrap(), "\u{964b}");
+
+        // Code points assigned after Unicode 3.2 are neither case folded nor
+        // normalized, and block the composition of their neighbors.
+        let query = |s| Profile::Nameprep.prepare(s, Unassigned::Allow);
+        assert_eq!(query("\u{1e9e}").unwrap(), "\u{1e9e}");
+        assert_eq!(query("\u{2c7c}").unwrap(), "\u{2c7c}");
+        assert_eq!(query("e\u{1dc0}\u{301}").unwrap(), "e\u{1dc0}\u{301}");
+        assert_eq!(query("\u{2c7c}\u{212b}").unwrap(), "\u{2c7c}\u{e5}");
+        assert_eq!(
+            Profile::Nameprep.prepare("\u{2c7c}", Unassigned::Prohibit),
+            Err(Error::Unassigned('\u{2c7c}'))
+        );
+        assert_eq!(
+            Profile::Saslprep
+                .prepare("\u{221}", Unassigned::Allow)
+                .unwrap(),
+            "\u{221}"
+        );
+    }
+}
@@ -0,0 +1,220 @@
+// Copyright 2012-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Removal of combining marks, for accent-insensitive matching.
+
+use crate::decompose::Decompositions;
+use crate::lookups::is_combining_mark;
+use crate::recompose::Recompositions;
+use crate::security::ScriptSet;
+use crate::tables::{SCRIPT_CYRILLIC, SCRIPT_GREEK, SCRIPT_LATIN};
+use core::{
+    fmt::{self, Write},
+    iter::FusedIterator,
+};
+
+/// The combining marks to remove.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MarkStripping {
+    /// Remove every combining mark (`General_Category` M), so that for
+    /// example `é` becomes `e` and `ü` becomes `u`.
+    All,
+    /// Only remove the marks which are diacritics: those of the Latin, Greek
+    /// and Cyrillic scripts, and the generic marks which aren't specific to a
+    /// script, like U+0301 COMBINING ACUTE ACCENT. The marks specific to
+    /// other scripts, such as the Indic vowel signs, the Hebrew and Arabic
+    /// points or the kana voicing marks, usually change the letters they
+    /// apply to and are kept.
+    Insignificant,
+}
+
+impl MarkStripping {
+    #[inline]
+    pub(crate) fn strips(self, c: char) -> bool {
+        if c.is_ascii() || !is_combining_mark(c) {
+            return false;
+        }
+        match self {
+            MarkStripping::All => true,
+            MarkStripping::Insignificant => {
+                let scripts = ScriptSet::of(c);
+                scripts == ScriptSet::ALL
+                    || scripts.contains(SCRIPT_LATIN)
+                    || scripts.contains(SCRIPT_GREEK)
+                    || scripts.contains(SCRIPT_CYRILLIC)
+            }
+        }
+    }
+}
+
+/// External iterator for a string's characters with their combining marks
+/// removed.
+///
+/// The string is decomposed to NFD first, so that the marks of precomposed
+/// characters are removed too, and the result is either left in NFD or
+/// recomposed to NFC.
+///
+/// ```rust
+/// use unicode_normalization::{MarkStripping, StripMarks, UnicodeNormalization};
+///
+/// assert_eq!("Cr\u{e8}me br\u{fb}l\u{e9}e".strip_marks().to_string(), "Creme brulee");
+/// assert_eq!("\u{212b}ngstr\u{f6}m".strip_marks().to_string(), "Angstrom");
+///
+/// // The Devanagari vowel sign is significant, the Latin diacritic isn't.
+/// let s = "\u{915}\u{93f} \u{1e0d}";
+/// assert_eq!(
+///     StripMarks::new_nfc(s.chars(), MarkStripping::Insignificant).to_string(),
+///     "\u{915}\u{93f} d"
+/// );
+/// assert_eq!(StripMarks::new_nfc(s.chars(), MarkStripping::All).to_string(), "\u{915} d");
+/// ```
+#[derive(Clone)]
+pub struct StripMarks<I> {
+    inner: Inner<I>,
+}
+
+#[derive(Clone)]
+enum Inner<I> {
+    Decomposed(MarkFilter<I>),
+    Recomposed(Recompositions<MarkFilter<I>>),
+}
+
+impl<I: Iterator<Item = char>> StripMarks<I> {
+    /// Create a new iterator that removes the given combining marks and
+    /// leaves the rest of the string in NFD.
+    #[inline]
+    pub fn new(iter: I, stripping: MarkStripping) -> StripMarks<I> {
+        StripMarks {
+            inner: Inner::Decomposed(MarkFilter::new(iter, stripping)),
+        }
+    }
+
+    /// Create a new iterator that removes the given combining marks and
+    /// recomposes the rest of the string to NFC.
+    ///
+    /// Note that this iterator can also be obtained with
+    /// [`MarkStripping::All`] by directly calling
+    /// [`.strip_marks()`](crate::UnicodeNormalization::strip_marks) on the
+    /// iterator.
+    #[inline]
+    pub fn new_nfc(iter: I, stripping: MarkStripping) -> StripMarks<I> {
+        StripMarks {
+            inner: Inner::Recomposed(Recompositions::new_canonical(MarkFilter::new(
+                iter, stripping,
+            ))),
+        }
+    }
+}
+
+impl<I: Iterator<Item = char>> Iterator for StripMarks<I> {
+    type Item = char;
+
+    #[inline]
+    fn next(&mut self) -> Option<char> {
+        match self.inner {
+            Inner::Decomposed(ref mut iter) => iter.next(),
+            Inner::Recomposed(ref mut iter) => iter.next(),
+        }
+    }
+}
+
+impl<I: Iterator<Item = char> + FusedIterator> FusedIterator for StripMarks<I> {}
+
+impl<I: Iterator<Item = char> + Clone> fmt::Display for StripMarks<I> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for c in self.clone() {
+            f.write_char(c)?;
+        }
+        Ok(())
+    }
+}
+
+/// The NFD of a string without the stripped marks. Removing marks keeps the
+/// remaining ones in canonical order, so the result is still in NFD.
+#[derive(Clone)]
+struct MarkFilter<I> {
+    iter: Decompositions<I>,
+    stripping: MarkStripping,
+}
+
+impl<I: Iterator<Item = char>> MarkFilter<I> {
+    #[inline]
+    fn new(iter: I, stripping: MarkStripping) -> MarkFilter<I> {
+        MarkFilter {
+            iter: Decompositions::new_canonical(iter),
+            stripping,
+        }
+    }
+}
+
+impl<I: Iterator<Item = char>> Iterator for MarkFilter<I> {
+    type Item = char;
+
+    #[inline]
+    fn next(&mut self) -> Option<char> {
+        let stripping = self.stripping;
+        self.iter.by_ref().find(|&c| !stripping.strips(c))
+    }
+}
+
+impl<I: Iterator<Item = char> + FusedIterator> FusedIterator for MarkFilter<I> {}
+
+#[cfg(test)]
+mod tests {
+    use super::{MarkStripping, StripMarks};
+
+    #[cfg(not(feature = "std"))]
+    use alloc::string::String;
+
+    fn nfd(s: &str, stripping: MarkStripping) -> String {
+        StripMarks::new(s.chars(), stripping).collect()
+    }
+
+    fn nfc(s: &str, stripping: MarkStripping) -> String {
+        StripMarks::new_nfc(s.chars(), stripping).collect()
+    }
+
+    #[test]
+    fn test_strip_all_marks() {
+        let all = MarkStripping::All;
+        assert_eq!(nfc("", all), "");
+        assert_eq!(nfc("abc", all), "abc");
+        assert_eq!(nfc("r\u{e9}sum\u{e9}", all), "resume");
+        assert_eq!(nfc("Ph\u{1edf}", all), "Pho");
+        assert_eq!(nfc("\u{1f00}\u{3b9}", all), "\u{3b1}\u{3b9}");
+        assert_eq!(nfc("\u{439}", all), "\u{438}");
+        assert_eq!(nfc("\u{2260}", all), "=");
+        assert_eq!(nfc("\u{5e9}\u{5c1}\u{5b8}", all), "\u{5e9}");
+        assert_eq!(nfc("\u{915}\u{93f}", all), "\u{915}");
+        // Hangul syllables aren't made of marks.
+        assert_eq!(nfc("\u{d55c}", all), "\u{d55c}");
+        assert_eq!(nfd("\u{d55c}", all), "\u{1112}\u{1161}\u{11ab}");
+        assert_eq!(nfd("\u{e9}\u{fb01}", all), "e\u{fb01}");
+    }
+
+    #[test]
+    fn test_strip_insignificant_marks() {
+        let insignificant = MarkStripping::Insignificant;
+        assert_eq!(nfc("r\u{e9}sum\u{e9}", insignificant), "resume");
+        assert_eq!(nfc("\u{1f04}", insignificant), "\u{3b1}");
+        assert_eq!(nfc("\u{439}", insignificant), "\u{438}");
+        // Hebrew and Arabic points, Indic vowel signs and kana voicing marks
+        // are kept.
+        assert_eq!(
+            nfc("\u{5e9}\u{5c1}\u{5b8}", insignificant),
+            "\u{5e9}\u{5b8}\u{5c1}"
+        );
+        assert_eq!(nfc("\u{628}\u{64e}", insignificant), "\u{628}\u{64e}");
+        assert_eq!(nfc("\u{915}\u{93f}", insignificant), "\u{915}\u{93f}");
+        assert_eq!(nfc("\u{b94}", insignificant), "\u{b94}");
+        assert_eq!(nfc("\u{304c}", insignificant), "\u{304c}");
+        assert_eq!(nfd("\u{304c}", insignificant), "\u{304b}\u{3099}");
+    }
+}
@@ -105,6 +105,78 @@ fn test_nfkc() {
     );
 }
 
+#[test]
+fn test_nfkc_casefold() {
+    macro_rules! t {
+        ($input: expr, $expected: expr) => {
+            assert_eq!($input.nfkc_casefold().to_string(), $expected);
+            assert!(crate::is_nfkc_casefold($expected));
+        };
+    }
+    t!("abc", "abc");
+    t!("ABC", "abc");
+    t!("\u{1e0b}\u{1c4}", "\u{1e0b}d\u{17e}");
+    t!("\u{2026}", "...");
+    t!("\u{2126}", "\u{3c9}");
+    t!("\u{212b}", "\u{e5}");
+    t!("Stra\u{df}e", "strasse");
+    t!("\u{1e9e}", "ss");
+    t!("\u{130}", "i\u{307}");
+    t!("\u{fb00}", "ff");
+    t!("\u{3392}", "mhz");
+    t!("\u{1f88}", "\u{1f00}\u{3b9}");
+    t!("A\u{301}", "\u{e1}");
+    // Default ignorables are removed, and characters around them may compose.
+    t!("soft\u{ad}hyphen", "softhyphen");
+    t!("a\u{200b}\u{301}", "\u{e1}");
+    t!("\u{e0001}\u{e0041}", "");
+}
+
+#[test]
+fn test_hfs_nfd() {
+    macro_rules! t {
+        ($input: expr, $expected: expr) => {
+            assert_eq!($input.hfs_nfd().to_string(), $expected);
+        };
+    }
+    t!("abc", "abc");
+    t!("caf\u{e9}", "cafe\u{301}");
+    t!("\u{1e0b}\u{323}", "d\u{323}\u{307}");
+    t!("\u{ac00}", "\u{1100}\u{1161}");
+    // The excluded ranges are left as they are.
+    t!("\u{2126}", "\u{2126}");
+    t!("\u{212b}", "\u{212b}");
+    t!("\u{2260}", "\u{2260}");
+    t!("\u{f900}", "\u{f900}");
+    t!("\u{2f800}", "\u{2f800}");
+    // Characters assigned after Unicode 3.2 are starters without decompositions.
+    t!("\u{1b06}", "\u{1b06}");
+    t!("e\u{1dca}\u{301}", "e\u{1dca}\u{301}");
+    t!("a\u{301}\u{316}", "a\u{316}\u{301}");
+}
+
+#[test]
+fn test_hfs_nfc() {
+    macro_rules! t {
+        ($input: expr, $expected: expr) => {
+            assert_eq!($input.hfs_nfc().to_string(), $expected);
+            assert_eq!(
+                $expected.hfs_nfd().to_string(),
+                $input.hfs_nfd().to_string()
+            );
+        };
+    }
+    t!("cafe\u{301}", "caf\u{e9}");
+    t!("\u{1100}\u{1161}", "\u{ac00}");
+    t!("A\u{30a}", "\u{c5}");
+    t!("\u{212b}", "\u{212b}");
+    // Never composes into the excluded ranges.
+    t!("=\u{338}", "=\u{338}");
+    t!("\u{2260}", "\u{2260}");
+    t!("\u{1b05}\u{1b35}", "\u{1b05}\u{1b35}");
+    t!("e\u{1dca}\u{301}", "e\u{1dca}\u{301}");
+}
+
 #[test]
 fn test_normalize_char() {
     assert_eq!('\u{2126}'.nfd().to_string(), "\u{3a9}")
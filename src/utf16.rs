@@ -0,0 +1,411 @@
+// Copyright 2012-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Normalization of UTF-16 encoded buffers.
+//!
+//! As in the [`utf8`](crate::utf8) module, runs of text that the quick check
+//! proves normalized are copied to the output verbatim, and only the segments
+//! around characters that may change are decoded and normalized.
+//!
+//! UTF-16 buffers coming from other systems may contain unpaired surrogates,
+//! so the normalization functions take an [`UnpairedSurrogates`] policy. The
+//! checking functions treat unpaired surrogates as normalized starters, which
+//! is consistent with both [`UnpairedSurrogates::Replace`] and
+//! [`UnpairedSurrogates::PassThrough`].
+//!
+//! ```rust
+//! use unicode_normalization::utf16::{self, UnpairedSurrogates};
+//!
+//! let input: Vec<u16> = "Introduccio\u{301}n".encode_utf16().collect();
+//! assert!(!utf16::is_nfc(&input));
+//!
+//! let mut out = Vec::new();
+//! utf16::nfc(&input, UnpairedSurrogates::Reject, &mut out).unwrap();
+//! assert_eq!(String::from_utf16(&out).unwrap(), "Introducci\u{f3}n");
+//! ```
+
+use crate::form::Form;
+use crate::lookups::canonical_combining_class;
+use crate::quick_check::{quick_check, IsNormalized};
+use crate::tables;
+use crate::UnicodeNormalization;
+use core::char::{self, REPLACEMENT_CHARACTER};
+use core::fmt;
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+/// How to handle unpaired surrogates in UTF-16 input.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum UnpairedSurrogates {
+    /// Fail with a [`Utf16Error`] without writing any output.
+    Reject,
+    /// Replace each unpaired surrogate with U+FFFD REPLACEMENT CHARACTER.
+    Replace,
+    /// Copy unpaired surrogates to the output unchanged. They are treated as
+    /// starters that never combine with their neighbors.
+    PassThrough,
+}
+
+/// An error returned when UTF-16 input contains an unpaired surrogate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Utf16Error {
+    valid_up_to: usize,
+    unpaired_surrogate: u16,
+}
+
+impl Utf16Error {
+    /// Returns the index of the unpaired surrogate in the input, which is
+    /// also the length of the well-formed prefix before it.
+    #[inline]
+    pub fn valid_up_to(&self) -> usize {
+        self.valid_up_to
+    }
+
+    /// Returns the unpaired surrogate which caused this error.
+    #[inline]
+    pub fn unpaired_surrogate(&self) -> u16 {
+        self.unpaired_surrogate
+    }
+}
+
+impl fmt::Display for Utf16Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "unpaired surrogate {:#x} found at index {}",
+            self.unpaired_surrogate, self.valid_up_to
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Utf16Error {}
+
+/// Decode the code point starting at `s[i]`, returning it along with its
+/// length in code units, or `Err` with the code unit if it is an unpaired
+/// surrogate.
+#[inline]
+fn decode_at(s: &[u16], i: usize) -> Result<(char, usize), u16> {
+    let u = s[i];
+    match u {
+        0xD800..=0xDBFF => match s.get(i + 1) {
+            Some(&low @ 0xDC00..=0xDFFF) => {
+                let c = 0x10000 + (((u as u32) - 0xD800) << 10) + ((low as u32) - 0xDC00);
+                Ok((char::from_u32(c).unwrap_or(REPLACEMENT_CHARACTER), 2))
+            }
+            _ => Err(u),
+        },
+        0xDC00..=0xDFFF => Err(u),
+        _ => Ok((char::from_u32(u as u32).unwrap_or(REPLACEMENT_CHARACTER), 1)),
+    }
+}
+
+/// Decode UTF-16, replacing unpaired surrogates with U+FFFD.
+#[inline]
+fn chars(s: &[u16]) -> impl Iterator<Item = char> + '_ {
+    char::decode_utf16(s.iter().cloned()).map(|r| r.unwrap_or(REPLACEMENT_CHARACTER))
+}
+
+/// Return the index of the first unpaired surrogate in `s`, or `s.len()`.
+#[inline]
+fn well_formed_len(s: &[u16]) -> usize {
+    let mut i = 0;
+    while i < s.len() {
+        match decode_at(s, i) {
+            Ok((_, len)) => i += len,
+            Err(_) => return i,
+        }
+    }
+    s.len()
+}
+
+/// The UTF-16 counterpart of `quick_check_span`, for well-formed input.
+#[inline]
+fn quick_check_span(s: &[u16], form: Form) -> usize {
+    let mut last_cc = 0u8;
+    let mut boundary = 0;
+    let mut i = 0;
+    while i < s.len() {
+        if s[i] <= 0x7f {
+            last_cc = 0;
+            boundary = i;
+            i += 1;
+            continue;
+        }
+
+        let (ch, len) = match decode_at(s, i) {
+            Ok(decoded) => decoded,
+            Err(_) => break,
+        };
+        let cc = canonical_combining_class(ch);
+        if last_cc > cc && cc != 0 {
+            return boundary;
+        }
+        match form.quick_check(ch) {
+            IsNormalized::Yes => {
+                if cc == 0 {
+                    boundary = i;
+                }
+            }
+            IsNormalized::No | IsNormalized::Maybe => return boundary,
+        }
+        last_cc = cc;
+        i += len;
+    }
+    s.len()
+}
+
+/// Normalize well-formed UTF-16, appending to `out`.
+fn normalize_well_formed(form: Form, mut s: &[u16], out: &mut Vec<u16>) {
+    loop {
+        let boundary = quick_check_span(s, form);
+        out.extend_from_slice(&s[..boundary]);
+        if boundary == s.len() {
+            return;
+        }
+
+        // See `form::normalize_to` for why the segment ends here.
+        let s_tail = &s[boundary..];
+        let mut end = s_tail.len();
+        let mut i = match decode_at(s_tail, 0) {
+            Ok((_, len)) => len,
+            Err(_) => 1,
+        };
+        while i < s_tail.len() {
+            match decode_at(s_tail, i) {
+                Ok((c, len)) => {
+                    if form.is_boundary_before(c) {
+                        end = i;
+                        break;
+                    }
+                    i += len;
+                }
+                Err(_) => break,
+            }
+        }
+
+        let mut buf = [0; 2];
+        form.normalize_chars(chars(&s_tail[..end]), |c| {
+            out.extend_from_slice(c.encode_utf16(&mut buf))
+        });
+        s = &s_tail[end..];
+    }
+}
+
+fn normalize(
+    form: Form,
+    mut input: &[u16],
+    surrogates: UnpairedSurrogates,
+    out: &mut Vec<u16>,
+) -> Result<(), Utf16Error> {
+    if surrogates == UnpairedSurrogates::Reject {
+        let valid_up_to = well_formed_len(input);
+        if valid_up_to < input.len() {
+            return Err(Utf16Error {
+                valid_up_to,
+                unpaired_surrogate: input[valid_up_to],
+            });
+        }
+    }
+
+    out.reserve(input.len());
+    loop {
+        // Both U+FFFD and an unpaired surrogate are starters that never
+        // combine with anything, so there is a normalization boundary on
+        // either side of them.
+        let valid_up_to = well_formed_len(input);
+        normalize_well_formed(form, &input[..valid_up_to], out);
+        if valid_up_to == input.len() {
+            return Ok(());
+        }
+        match surrogates {
+            UnpairedSurrogates::Replace => out.push(REPLACEMENT_CHARACTER as u16),
+            _ => out.push(input[valid_up_to]),
+        }
+        input = &input[valid_up_to + 1..];
+    }
+}
+
+/// Append the Unicode Normalization Form C of the UTF-16 `input` to `out`.
+///
+/// An error is only returned for [`UnpairedSurrogates::Reject`].
+#[inline]
+pub fn nfc(
+    input: &[u16],
+    surrogates: UnpairedSurrogates,
+    out: &mut Vec<u16>,
+) -> Result<(), Utf16Error> {
+    normalize(Form::Nfc, input, surrogates, out)
+}
+
+/// Append the Unicode Normalization Form KC of the UTF-16 `input` to `out`.
+///
+/// An error is only returned for [`UnpairedSurrogates::Reject`].
+#[inline]
+pub fn nfkc(
+    input: &[u16],
+    surrogates: UnpairedSurrogates,
+    out: &mut Vec<u16>,
+) -> Result<(), Utf16Error> {
+    normalize(Form::Nfkc, input, surrogates, out)
+}
+
+/// Append the Unicode Normalization Form D of the UTF-16 `input` to `out`.
+///
+/// An error is only returned for [`UnpairedSurrogates::Reject`].
+#[inline]
+pub fn nfd(
+    input: &[u16],
+    surrogates: UnpairedSurrogates,
+    out: &mut Vec<u16>,
+) -> Result<(), Utf16Error> {
+    normalize(Form::Nfd, input, surrogates, out)
+}
+
+/// Append the Unicode Normalization Form KD of the UTF-16 `input` to `out`.
+///
+/// An error is only returned for [`UnpairedSurrogates::Reject`].
+#[inline]
+pub fn nfkd(
+    input: &[u16],
+    surrogates: UnpairedSurrogates,
+    out: &mut Vec<u16>,
+) -> Result<(), Utf16Error> {
+    normalize(Form::Nfkd, input, surrogates, out)
+}
+
+#[inline]
+fn is_normalized(form: Form, s: &[u16]) -> bool {
+    let quick = match form {
+        Form::Nfc => is_nfc_quick(s),
+        Form::Nfd => is_nfd_quick(s),
+        Form::Nfkc => is_nfkc_quick(s),
+        Form::Nfkd => is_nfkd_quick(s),
+    };
+    match quick {
+        IsNormalized::Yes => true,
+        IsNormalized::No => false,
+        IsNormalized::Maybe => match form {
+            Form::Nfc => chars(s).eq(chars(s).nfc()),
+            Form::Nfd => chars(s).eq(chars(s).nfd()),
+            Form::Nfkc => chars(s).eq(chars(s).nfkc()),
+            Form::Nfkd => chars(s).eq(chars(s).nfkd()),
+        },
+    }
+}
+
+/// Quickly check if a UTF-16 buffer is in NFC, potentially returning
+/// `IsNormalized::Maybe` if further checks are necessary.
+#[inline]
+pub fn is_nfc_quick(s: &[u16]) -> IsNormalized {
+    quick_check(chars(s), tables::qc_nfc, false)
+}
+
+/// Quickly check if a UTF-16 buffer is in NFKC.
+#[inline]
+pub fn is_nfkc_quick(s: &[u16]) -> IsNormalized {
+    quick_check(chars(s), tables::qc_nfkc, false)
+}
+
+/// Quickly check if a UTF-16 buffer is in NFD.
+#[inline]
+pub fn is_nfd_quick(s: &[u16]) -> IsNormalized {
+    quick_check(chars(s), tables::qc_nfd, false)
+}
+
+/// Quickly check if a UTF-16 buffer is in NFKD.
+#[inline]
+pub fn is_nfkd_quick(s: &[u16]) -> IsNormalized {
+    quick_check(chars(s), tables::qc_nfkd, false)
+}
+
+/// Authoritatively check if a UTF-16 buffer is in NFC.
+#[inline]
+pub fn is_nfc(s: &[u16]) -> bool {
+    is_normalized(Form::Nfc, s)
+}
+
+/// Authoritatively check if a UTF-16 buffer is in NFKC.
+#[inline]
+pub fn is_nfkc(s: &[u16]) -> bool {
+    is_normalized(Form::Nfkc, s)
+}
+
+/// Authoritatively check if a UTF-16 buffer is in NFD.
+#[inline]
+pub fn is_nfd(s: &[u16]) -> bool {
+    is_normalized(Form::Nfd, s)
+}
+
+/// Authoritatively check if a UTF-16 buffer is in NFKD.
+#[inline]
+pub fn is_nfkd(s: &[u16]) -> bool {
+    is_normalized(Form::Nfkd, s)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_nfc, is_nfd, nfc, nfd, nfkd, UnpairedSurrogates, Utf16Error};
+    use crate::UnicodeNormalization;
+
+    #[cfg(not(feature = "std"))]
+    use alloc::{string::String, vec::Vec};
+
+    fn utf16(s: &str) -> Vec<u16> {
+        s.encode_utf16().collect()
+    }
+
+    #[test]
+    fn test_astral() {
+        // U+1D15E MUSICAL SYMBOL HALF NOTE decomposes to a pair of astral
+        // characters, and U+1D165 has a nonzero combining class.
+        let input = utf16("a\u{1d15e}\u{1d165}\u{1d16d}\u{1d165}b");
+        assert!(!is_nfc(&input));
+        assert!(!is_nfd(&input));
+
+        let mut out = Vec::new();
+        nfd(&input, UnpairedSurrogates::Reject, &mut out).unwrap();
+        let expected: String = "a\u{1d15e}\u{1d165}\u{1d16d}\u{1d165}b".nfd().collect();
+        assert_eq!(out, utf16(&expected));
+        assert!(is_nfd(&out));
+    }
+
+    #[test]
+    fn test_unpaired_surrogates() {
+        let mut input = utf16("e");
+        input.push(0xdc00);
+        input.extend(utf16("\u{301}e\u{301}"));
+        input.push(0xd800);
+
+        let mut out = Vec::new();
+        assert_eq!(
+            nfc(&input, UnpairedSurrogates::Reject, &mut out),
+            Err(Utf16Error {
+                valid_up_to: 1,
+                unpaired_surrogate: 0xdc00
+            })
+        );
+        assert!(out.is_empty());
+
+        nfc(&input, UnpairedSurrogates::Replace, &mut out).unwrap();
+        assert_eq!(out, utf16("e\u{fffd}\u{301}\u{e9}\u{fffd}"));
+
+        out.clear();
+        nfkd(&input, UnpairedSurrogates::PassThrough, &mut out).unwrap();
+        let mut expected = utf16("e");
+        expected.push(0xdc00);
+        expected.extend(utf16("\u{301}e\u{301}"));
+        expected.push(0xd800);
+        assert_eq!(out, expected);
+        assert!(is_nfd(&out));
+        assert!(!is_nfc(&out));
+    }
+}
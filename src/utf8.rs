@@ -0,0 +1,150 @@
+// Copyright 2012-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Normalization of UTF-8 encoded byte slices.
+//!
+//! Unlike the iterator adapters, these functions do not decode their whole
+//! input to `char`s: runs of text that the quick check proves normalized are
+//! copied to the output as byte spans, and only the segments around characters
+//! that may change are decoded and normalized.
+//!
+//! Input which is not well-formed UTF-8 is either rejected, by [`nfc`] and
+//! friends, or has its ill-formed sequences replaced by U+FFFD REPLACEMENT
+//! CHARACTER, by [`nfc_lossy`] and friends. Replacement follows the same
+//! "substitution of maximal subparts" practice as `String::from_utf8_lossy`,
+//! and happens in the same pass as normalization.
+//!
+//! ```rust
+//! use unicode_normalization::utf8;
+//!
+//! let mut out = Vec::new();
+//! utf8::nfc(b"Introduccio\xcc\x81n", &mut out).unwrap();
+//! assert_eq!(out, "Introducci\u{f3}n".as_bytes());
+//!
+//! let err = utf8::nfc(b"caf\xc3", &mut out).unwrap_err();
+//! assert_eq!(err.valid_up_to(), 3);
+//!
+//! out.clear();
+//! utf8::nfc_lossy(b"cafe\xcc\x81 \xc3", &mut out);
+//! assert_eq!(out, "caf\u{e9} \u{fffd}".as_bytes());
+//! ```
+
+use crate::form::{normalize_to, Form};
+use core::str;
+
+pub use core::str::Utf8Error;
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+const REPLACEMENT_CHARACTER: &str = "\u{FFFD}";
+
+#[inline]
+fn normalize(form: Form, input: &[u8], out: &mut Vec<u8>) -> Result<(), Utf8Error> {
+    let s = str::from_utf8(input)?;
+    out.reserve(s.len());
+    normalize_to(form, s, out);
+    Ok(())
+}
+
+#[inline]
+fn normalize_lossy(form: Form, mut input: &[u8], out: &mut Vec<u8>) {
+    out.reserve(input.len());
+    loop {
+        match str::from_utf8(input) {
+            Ok(s) => {
+                normalize_to(form, s, out);
+                return;
+            }
+            Err(e) => {
+                let (valid, rest) = input.split_at(e.valid_up_to());
+                // U+FFFD is a starter which is normalized in every form, so
+                // there is a normalization boundary on either side of it and
+                // the valid chunks can be normalized independently.
+                //
+                // `valid` always passes; checking it again is cheap next to
+                // normalizing it and saves us an `unsafe` block.
+                if let Ok(s) = str::from_utf8(valid) {
+                    normalize_to(form, s, out);
+                }
+                out.extend_from_slice(REPLACEMENT_CHARACTER.as_bytes());
+                match e.error_len() {
+                    Some(len) => input = &rest[len..],
+                    // The input ends in the middle of a sequence.
+                    None => return,
+                }
+            }
+        }
+    }
+}
+
+/// Append the Unicode Normalization Form C of the UTF-8 `input` to `out`.
+///
+/// If `input` is not valid UTF-8, nothing is written and the error describes
+/// where the first invalid sequence starts.
+#[inline]
+pub fn nfc(input: &[u8], out: &mut Vec<u8>) -> Result<(), Utf8Error> {
+    normalize(Form::Nfc, input, out)
+}
+
+/// Append the Unicode Normalization Form KC of the UTF-8 `input` to `out`.
+///
+/// If `input` is not valid UTF-8, nothing is written and the error describes
+/// where the first invalid sequence starts.
+#[inline]
+pub fn nfkc(input: &[u8], out: &mut Vec<u8>) -> Result<(), Utf8Error> {
+    normalize(Form::Nfkc, input, out)
+}
+
+/// Append the Unicode Normalization Form D of the UTF-8 `input` to `out`.
+///
+/// If `input` is not valid UTF-8, nothing is written and the error describes
+/// where the first invalid sequence starts.
+#[inline]
+pub fn nfd(input: &[u8], out: &mut Vec<u8>) -> Result<(), Utf8Error> {
+    normalize(Form::Nfd, input, out)
+}
+
+/// Append the Unicode Normalization Form KD of the UTF-8 `input` to `out`.
+///
+/// If `input` is not valid UTF-8, nothing is written and the error describes
+/// where the first invalid sequence starts.
+#[inline]
+pub fn nfkd(input: &[u8], out: &mut Vec<u8>) -> Result<(), Utf8Error> {
+    normalize(Form::Nfkd, input, out)
+}
+
+/// Append the Unicode Normalization Form C of the UTF-8 `input` to `out`,
+/// replacing ill-formed sequences with U+FFFD REPLACEMENT CHARACTER.
+#[inline]
+pub fn nfc_lossy(input: &[u8], out: &mut Vec<u8>) {
+    normalize_lossy(Form::Nfc, input, out)
+}
+
+/// Append the Unicode Normalization Form KC of the UTF-8 `input` to `out`,
+/// replacing ill-formed sequences with U+FFFD REPLACEMENT CHARACTER.
+#[inline]
+pub fn nfkc_lossy(input: &[u8], out: &mut Vec<u8>) {
+    normalize_lossy(Form::Nfkc, input, out)
+}
+
+/// Append the Unicode Normalization Form D of the UTF-8 `input` to `out`,
+/// replacing ill-formed sequences with U+FFFD REPLACEMENT CHARACTER.
+#[inline]
+pub fn nfd_lossy(input: &[u8], out: &mut Vec<u8>) {
+    normalize_lossy(Form::Nfd, input, out)
+}
+
+/// Append the Unicode Normalization Form KD of the UTF-8 `input` to `out`,
+/// replacing ill-formed sequences with U+FFFD REPLACEMENT CHARACTER.
+#[inline]
+pub fn nfkd_lossy(input: &[u8], out: &mut Vec<u8>) {
+    normalize_lossy(Form::Nfkd, input, out)
+}
@@ -0,0 +1,176 @@
+// Copyright 2012-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use crate::form::Form;
+use crate::stream::Incremental;
+use core::fmt::{self, Write};
+
+#[cfg(not(feature = "std"))]
+use alloc::string::String;
+
+/// A [`fmt::Write`] adapter which normalizes everything written through it
+/// before passing it on to an inner writer.
+///
+/// Text is passed on up to the last normalization boundary written so far;
+/// the rest is held back, since it may still reorder or compose with what is
+/// written next. Call [`finish`](NormalizingWriter::finish) once all the text
+/// has been written to pass on the held back tail.
+///
+/// ```rust
+/// use std::fmt::Write;
+/// use unicode_normalization::NormalizingWriter;
+///
+/// let mut writer = NormalizingWriter::nfc(String::new());
+/// write!(writer, "Introduccio{}n", '\u{301}').unwrap();
+/// writer.finish().unwrap();
+/// assert_eq!(writer.into_inner(), "Introducci\u{f3}n");
+/// ```
+#[derive(Clone, Debug)]
+pub struct NormalizingWriter<W> {
+    inner: W,
+    normalizer: Incremental,
+    // Normalized text on its way to `inner`.
+    buffer: String,
+}
+
+impl<W: Write> NormalizingWriter<W> {
+    #[inline]
+    fn new(inner: W, form: Form) -> Self {
+        NormalizingWriter {
+            inner,
+            normalizer: Incremental::new(form, false),
+            buffer: String::new(),
+        }
+    }
+
+    /// Create a writer which converts text to Unicode Normalization Form C.
+    #[inline]
+    pub fn nfc(inner: W) -> Self {
+        NormalizingWriter::new(inner, Form::Nfc)
+    }
+
+    /// Create a writer which converts text to Unicode Normalization Form KC.
+    #[inline]
+    pub fn nfkc(inner: W) -> Self {
+        NormalizingWriter::new(inner, Form::Nfkc)
+    }
+
+    /// Create a writer which converts text to Unicode Normalization Form D.
+    #[inline]
+    pub fn nfd(inner: W) -> Self {
+        NormalizingWriter::new(inner, Form::Nfd)
+    }
+
+    /// Create a writer which converts text to Unicode Normalization Form KD.
+    #[inline]
+    pub fn nfkd(inner: W) -> Self {
+        NormalizingWriter::new(inner, Form::Nfkd)
+    }
+
+    /// Pass the text held back since the last normalization boundary on to
+    /// the inner writer, as the text has ended.
+    ///
+    /// Anything written afterwards is normalized as a new, separate text.
+    pub fn finish(&mut self) -> fmt::Result {
+        self.normalizer.finish(&mut self.buffer);
+        self.flush_buffer()
+    }
+
+    /// Gets a reference to the inner writer.
+    #[inline]
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Gets a mutable reference to the inner writer.
+    ///
+    /// Writing to it directly will interleave with the normalized text.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Unwraps this writer, returning the inner writer.
+    ///
+    /// Any text held back since [`finish`](NormalizingWriter::finish) was last
+    /// called is lost.
+    #[inline]
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+
+    fn flush_buffer(&mut self) -> fmt::Result {
+        let result = self.inner.write_str(&self.buffer);
+        self.buffer.clear();
+        result
+    }
+}
+
+impl<W: Write> Write for NormalizingWriter<W> {
+    #[inline]
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.normalizer.push_str(s, &mut self.buffer);
+        self.flush_buffer()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::NormalizingWriter;
+    use crate::UnicodeNormalization;
+    use core::fmt::{self, Write};
+
+    #[cfg(not(feature = "std"))]
+    use alloc::string::String;
+
+    #[test]
+    fn test_write_chunks() {
+        let s = "a\u{300}\u{305}\u{315}\u{5ae}b \u{1e0b}\u{323}\u{2026} \u{1100}\u{1161}\u{11a8}";
+        let mut writer = NormalizingWriter::nfkc(String::new());
+        for c in s.chars() {
+            write!(writer, "{}", c).unwrap();
+        }
+        writer.finish().unwrap();
+        assert_eq!(writer.get_ref(), &s.nfkc().collect::<String>());
+
+        // The writer is reusable after `finish`.
+        writer.write_str("\u{1e0b}").unwrap();
+        writer.write_str("\u{323}").unwrap();
+        writer.finish().unwrap();
+        let expected = s.nfkc().collect::<String>() + "\u{1e0d}\u{307}";
+        assert_eq!(writer.into_inner(), expected);
+    }
+
+    #[test]
+    fn test_holds_back_tail() {
+        let mut writer = NormalizingWriter::nfd(String::new());
+        writer.write_str("ab\u{301}").unwrap();
+        assert_eq!(writer.get_ref(), "a");
+        writer.write_str("\u{316}").unwrap();
+        assert_eq!(writer.get_ref(), "a");
+        writer.finish().unwrap();
+        assert_eq!(writer.get_ref(), "ab\u{316}\u{301}");
+    }
+
+    #[test]
+    fn test_inner_error() {
+        struct Failing;
+
+        impl Write for Failing {
+            fn write_str(&mut self, _: &str) -> fmt::Result {
+                Err(fmt::Error)
+            }
+        }
+
+        let mut writer = NormalizingWriter::nfc(Failing);
+        assert!(writer.write_str("abc").is_err());
+        assert!(writer.finish().is_err());
+    }
+}
@@ -3,8 +3,10 @@ use unicode_normalization::UnicodeNormalization;
 use unicode_normalization::__test_api::stream_safe;
 
 mod data {
+    pub mod idna_tests;
     pub mod normalization_tests;
 }
+use crate::data::idna_tests::IDNA_TESTS;
 use crate::data::normalization_tests::NORMALIZATION_TESTS;
 
 #[test]
@@ -103,3 +105,401 @@ fn test_quick_check() {
         }
     }
 }
+
+#[test]
+fn test_nfkc_casefold() {
+    use unicode_normalization::{is_nfkc_casefold, is_nfkc_casefold_quick, IsNormalized};
+
+    for test in NORMALIZATION_TESTS {
+        let folded = test.source.nfkc_casefold().collect::<String>();
+        for &s in &[test.nfc, test.nfd, test.nfkc, test.nfkd] {
+            assert_eq!(s.nfkc_casefold().collect::<String>(), folded);
+        }
+        assert!(is_nfkc_casefold(&folded));
+    }
+
+    // The mapping is closed, and the quick check never rejects its results.
+    for c in (0..0x110000).filter_map(std::char::from_u32) {
+        let folded = c.nfkc_casefold().collect::<String>();
+        assert_eq!(folded.nfkc_casefold().collect::<String>(), folded);
+        assert_ne!(is_nfkc_casefold_quick(folded.chars()), IsNormalized::No);
+        assert_eq!(
+            is_nfkc_casefold(c.encode_utf8(&mut [0; 4])),
+            folded == c.to_string()
+        );
+    }
+}
+
+#[test]
+fn test_identifier_closure() {
+    use unicode_normalization::identifier::{is_identifier, is_xid_continue, is_xid_start};
+
+    for c in (0..0x110000).filter_map(std::char::from_u32) {
+        if is_xid_start(c) {
+            for s in &[c.nfc().collect::<String>(), c.nfkc().collect::<String>()] {
+                assert!(is_identifier(s), "{:04X}", c as u32);
+            }
+        }
+        if is_xid_continue(c) {
+            for s in &[c.nfc().collect::<String>(), c.nfkc().collect::<String>()] {
+                assert!(s.chars().all(is_xid_continue), "{:04X}", c as u32);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_official_quick_span() {
+    use unicode_normalization::{
+        is_nfc, is_nfd, is_nfkc, is_nfkd, nfc_quick_span, nfd_quick_span, nfkc_quick_span,
+        nfkd_quick_span,
+    };
+
+    macro_rules! check {
+        ($span: ident, $is_normalized: ident, $method: ident, $input: expr, $expected: expr) => {
+            let offset = $span($input);
+            assert!($is_normalized(&$input[..offset]));
+            let normalized =
+                $input[..offset].to_string() + &$input[offset..].$method().collect::<String>();
+            assert_eq!(normalized, $expected);
+            if $input == $expected {
+                continue;
+            }
+            assert!(offset < $input.len());
+        };
+    }
+
+    for test in NORMALIZATION_TESTS {
+        for &s in &[test.source, test.nfc, test.nfd] {
+            check!(nfc_quick_span, is_nfc, nfc, s, test.nfc);
+        }
+        for &s in &[test.source, test.nfc, test.nfd] {
+            check!(nfd_quick_span, is_nfd, nfd, s, test.nfd);
+        }
+        for &s in &[test.source, test.nfc, test.nfd, test.nfkc, test.nfkd] {
+            check!(nfkc_quick_span, is_nfkc, nfkc, s, test.nfkc);
+        }
+        for &s in &[test.source, test.nfc, test.nfd, test.nfkc, test.nfkd] {
+            check!(nfkd_quick_span, is_nfkd, nfkd, s, test.nfkd);
+        }
+    }
+}
+
+#[test]
+fn test_official_equivalent() {
+    use unicode_normalization::{canonically_equivalent, compatibility_equivalent};
+
+    for test in NORMALIZATION_TESTS {
+        for &a in &[test.source, test.nfc, test.nfd] {
+            for &b in &[test.source, test.nfc, test.nfd] {
+                assert!(canonically_equivalent(a, b));
+            }
+            // An extra mark on one side is never equivalent.
+            assert!(!canonically_equivalent(
+                a,
+                &(test.nfd.to_string() + "\u{301}")
+            ));
+        }
+        for &a in &[test.source, test.nfc, test.nfd, test.nfkc, test.nfkd] {
+            for &b in &[test.nfkc, test.nfkd] {
+                assert!(compatibility_equivalent(a, b));
+            }
+        }
+        assert_eq!(
+            canonically_equivalent(test.source, test.nfkd),
+            test.nfd == test.nfkd
+        );
+    }
+}
+
+#[test]
+fn test_official_push_str() {
+    use unicode_normalization::{NfcString, NfdString, NfkcString, NfkdString};
+
+    macro_rules! check {
+        ($string: ident, $input: expr, $expected: expr) => {
+            let mut s = $string::new();
+            for c in $input.chars() {
+                s.push_str(c.encode_utf8(&mut [0; 4]));
+            }
+            assert_eq!(s.as_str(), $expected);
+            assert_eq!($string::from($input).as_str(), $expected);
+        };
+    }
+
+    for test in NORMALIZATION_TESTS {
+        check!(NfcString, test.source, test.nfc);
+        check!(NfdString, test.source, test.nfd);
+        check!(NfkcString, test.source, test.nfkc);
+        check!(NfkdString, test.source, test.nfkd);
+    }
+}
+
+#[test]
+fn test_official_concat() {
+    use unicode_normalization::{concat_nfc, concat_nfd, NfcStr, NfdStr};
+
+    macro_rules! check {
+        ($concat: ident, $str: ident, $method: ident, $a: expr, $b: expr) => {
+            if let (Some(a), Some(b)) = ($str::new($a), $str::new($b)) {
+                let expected = ($a.to_string() + $b).$method().collect::<String>();
+                assert_eq!($concat(a, b).as_str(), expected);
+            }
+        };
+    }
+
+    for test in NORMALIZATION_TESTS {
+        for (i, _) in test.source.char_indices() {
+            let (a, b) = test.source.split_at(i);
+            check!(concat_nfc, NfcStr, nfc, a, b);
+            check!(concat_nfd, NfdStr, nfd, a, b);
+        }
+        for &s in &[test.nfc, test.nfd] {
+            for (i, _) in s.char_indices() {
+                let (a, b) = s.split_at(i);
+                check!(concat_nfc, NfcStr, nfc, a, b);
+                check!(concat_nfd, NfdStr, nfd, a, b);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_official_cow() {
+    use std::borrow::Cow;
+    use unicode_normalization::{nfc_cow, nfd_cow, nfkc_cow, nfkd_cow};
+
+    macro_rules! check {
+        ($method: ident, $input: expr, $expected: expr) => {
+            let normalized = $method($input);
+            assert_eq!(normalized, $expected);
+            if let Cow::Owned(_) = normalized {
+                assert_ne!($input, $expected);
+            }
+        };
+    }
+
+    for test in NORMALIZATION_TESTS {
+        for &s in &[test.source, test.nfc, test.nfd] {
+            check!(nfc_cow, s, test.nfc);
+            check!(nfd_cow, s, test.nfd);
+        }
+        for &s in &[test.source, test.nfc, test.nfd, test.nfkc, test.nfkd] {
+            check!(nfkc_cow, s, test.nfkc);
+            check!(nfkd_cow, s, test.nfkd);
+        }
+    }
+}
+
+#[test]
+fn test_official_in_place() {
+    use unicode_normalization::{
+        normalize_nfc_chars_in_place, normalize_nfc_in_place, normalize_nfd_chars_in_place,
+        normalize_nfd_in_place, normalize_nfkc_chars_in_place, normalize_nfkc_in_place,
+        normalize_nfkd_chars_in_place, normalize_nfkd_in_place,
+    };
+
+    macro_rules! check {
+        ($method: ident, $chars_method: ident, $input: expr, $expected: expr) => {
+            let mut s = $input.to_string();
+            $method(&mut s);
+            assert_eq!(s, $expected);
+
+            let mut v = $input.chars().collect::<Vec<_>>();
+            $chars_method(&mut v);
+            assert_eq!(v, $expected.chars().collect::<Vec<_>>());
+        };
+    }
+
+    for test in NORMALIZATION_TESTS {
+        for &s in &[test.source, test.nfc, test.nfd] {
+            check!(
+                normalize_nfc_in_place,
+                normalize_nfc_chars_in_place,
+                s,
+                test.nfc
+            );
+            check!(
+                normalize_nfd_in_place,
+                normalize_nfd_chars_in_place,
+                s,
+                test.nfd
+            );
+        }
+        for &s in &[test.source, test.nfc, test.nfd, test.nfkc, test.nfkd] {
+            check!(
+                normalize_nfkc_in_place,
+                normalize_nfkc_chars_in_place,
+                s,
+                test.nfkc
+            );
+            check!(
+                normalize_nfkd_in_place,
+                normalize_nfkd_chars_in_place,
+                s,
+                test.nfkd
+            );
+        }
+    }
+}
+
+#[test]
+fn test_official_normalizer() {
+    use unicode_normalization::Normalizer;
+
+    macro_rules! check {
+        ($method: ident, $input: expr, $expected: expr) => {
+            let mut normalizer = Normalizer::$method();
+            let mut out = String::new();
+            let mut buf = [0; 4];
+            for c in $input.chars() {
+                normalizer.push_str(c.encode_utf8(&mut buf), &mut out);
+            }
+            normalizer.finish(&mut out);
+            assert_eq!(out, $expected);
+        };
+    }
+
+    for test in NORMALIZATION_TESTS {
+        check!(nfc, test.source, test.nfc);
+        check!(nfd, test.source, test.nfd);
+        check!(nfkc, test.source, test.nfkc);
+        check!(nfkd, test.source, test.nfkd);
+    }
+}
+
+#[test]
+fn test_official_utf8() {
+    use unicode_normalization::utf8;
+
+    macro_rules! check {
+        ($method: ident, $input: expr, $expected: expr) => {
+            let mut out = b"prefix".to_vec();
+            utf8::$method($input.as_bytes(), &mut out).unwrap();
+            assert_eq!(&out[6..], $expected.as_bytes());
+        };
+    }
+
+    for test in NORMALIZATION_TESTS {
+        for &s in &[test.source, test.nfc, test.nfd] {
+            check!(nfc, s, test.nfc);
+            check!(nfd, s, test.nfd);
+        }
+        for &s in &[test.source, test.nfc, test.nfd, test.nfkc, test.nfkd] {
+            check!(nfkc, s, test.nfkc);
+            check!(nfkd, s, test.nfkd);
+        }
+    }
+}
+
+#[test]
+fn test_utf8_invalid() {
+    use unicode_normalization::utf8;
+
+    let mut out = Vec::new();
+    let err = utf8::nfc(b"abc\xe2\x80de", &mut out).unwrap_err();
+    assert_eq!(err.valid_up_to(), 3);
+    assert!(out.is_empty());
+}
+
+#[test]
+fn test_utf8_lossy() {
+    use unicode_normalization::utf8;
+
+    // Example from "U+FFFD Substitution of Maximal Subparts" in chapter 3 of
+    // the Unicode Standard.
+    let mut out = Vec::new();
+    utf8::nfc_lossy(
+        b"\x61\xF1\x80\x80\xE1\x80\xC2\x62\x80\x63\x80\xBF\x64",
+        &mut out,
+    );
+    assert_eq!(
+        out,
+        "a\u{FFFD}\u{FFFD}\u{FFFD}b\u{FFFD}c\u{FFFD}\u{FFFD}d".as_bytes()
+    );
+
+    // Marks never compose across a replacement character.
+    out.clear();
+    utf8::nfc_lossy(b"e\xff\xcc\x81e\xcc\x81", &mut out);
+    assert_eq!(out, "e\u{FFFD}\u{301}\u{e9}".as_bytes());
+
+    out.clear();
+    utf8::nfkd_lossy(b"\xe2\x80\xa6\xed\xa0\x80\xe2\x80", &mut out);
+    assert_eq!(out, "...\u{FFFD}\u{FFFD}\u{FFFD}\u{FFFD}".as_bytes());
+
+    for test in NORMALIZATION_TESTS {
+        out.clear();
+        utf8::nfd_lossy(test.source.as_bytes(), &mut out);
+        assert_eq!(out, test.nfd.as_bytes());
+    }
+}
+
+#[test]
+fn test_official_utf16() {
+    use unicode_normalization::utf16::{self, UnpairedSurrogates};
+
+    macro_rules! check {
+        ($method: ident, $is_normalized: ident, $input: expr, $expected: expr) => {
+            let input: Vec<u16> = $input.encode_utf16().collect();
+            let expected: Vec<u16> = $expected.encode_utf16().collect();
+            let mut out = Vec::new();
+            utf16::$method(&input, UnpairedSurrogates::Reject, &mut out).unwrap();
+            assert_eq!(out, expected);
+            assert_eq!(utf16::$is_normalized(&input), input == expected);
+        };
+    }
+
+    for test in NORMALIZATION_TESTS {
+        for &s in &[test.source, test.nfc, test.nfd] {
+            check!(nfc, is_nfc, s, test.nfc);
+            check!(nfd, is_nfd, s, test.nfd);
+        }
+        for &s in &[test.source, test.nfc, test.nfd, test.nfkc, test.nfkd] {
+            check!(nfkc, is_nfkc, s, test.nfkc);
+            check!(nfkd, is_nfkd, s, test.nfkd);
+        }
+    }
+}
+
+#[test]
+fn test_official_idna() {
+    use unicode_normalization::idna::{Config, Processing};
+
+    // These invariants come from the CONFORMANCE section of
+    // https://www.unicode.org/Public/idna/latest/IdnaTestV2.txt, where every
+    // flag is set. Only whether there is an error is checked, not its code.
+    // X4_2 flags an empty label in toUnicode for compatibility with older
+    // versions of the file, where it was an error of toASCII only.
+    let nontransitional = Config::new();
+    let transitional = Config::new().processing(Processing::Transitional);
+    for test in IDNA_TESTS {
+        let (unicode, result) = nontransitional.to_unicode(test.source);
+        assert_eq!(unicode, test.to_unicode, "toUnicode({:?})", test.source);
+        assert_eq!(
+            result.is_ok(),
+            test.to_unicode_status.iter().all(|&s| s == "X4_2"),
+            "toUnicode({:?}) returned {:?}, expected {:?}",
+            test.source,
+            result,
+            test.to_unicode_status
+        );
+
+        for &(config, expected, status) in &[
+            (&nontransitional, test.to_ascii_n, test.to_ascii_n_status),
+            (&transitional, test.to_ascii_t, test.to_ascii_t_status),
+        ] {
+            let ascii = config.to_ascii(test.source);
+            if status.is_empty() {
+                assert_eq!(ascii.as_ref().map(|s| &s[..]), Ok(expected));
+            } else {
+                assert!(
+                    ascii.is_err(),
+                    "toASCII({:?}) returned {:?}, expected {:?}",
+                    test.source,
+                    ascii,
+                    status
+                );
+            }
+        }
+    }
+}